@@ -16,8 +16,7 @@ use crate::{Fossology, FossologyError, FossologyResponse};
 /// - Response is not [`Token`].
 pub fn tokens(fossology: &Fossology, params: &TokensParameters) -> Result<Token, FossologyError> {
     let response = fossology
-        .client
-        .post(&format!("{}/tokens", fossology.uri))
+        .init_post("tokens")
         .json(&params)
         .send()?
         .json::<FossologyResponse<Token>>()?;
@@ -64,100 +63,50 @@ impl TokensParameters {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Token {
     #[serde(rename = "Authorization")]
     pub authorization: String,
 }
 
 #[cfg(test)]
-pub(crate) mod test {
+mod test {
     use chrono::{Duration, Utc};
-    use rand::{distributions::Alphanumeric, Rng};
 
-    use super::*;
-
-    pub fn create_test_fossology_with_writetoken(uri: &str) -> Fossology {
-        let fossology = Fossology::new(uri, "token").unwrap();
-        let token_name = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(10)
-            .map(char::from)
-            .collect::<String>();
-
-        let expiration_date = Utc::now()
-            .checked_add_signed(Duration::days(5))
-            .unwrap()
-            .naive_utc()
-            .date();
-
-        let params = TokensParameters::new(
-            "fossy",
-            "fossy",
-            &token_name,
-            TokenScope::Write,
-            expiration_date,
-        );
-
-        let token = tokens(&fossology, &params).unwrap();
-
-        Fossology::new(
-            "http://localhost:8080/repo/api/v1",
-            token.authorization.strip_prefix("Bearer ").unwrap(),
-        )
-        .unwrap()
-    }
-
-    #[test]
-    fn generate_read_token() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
+    use crate::testing::MockFossology;
 
-        let token_name = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(10)
-            .map(char::from)
-            .collect::<String>();
+    use super::*;
 
-        let params = TokensParameters::new(
+    fn params(token_scope: TokenScope) -> TokensParameters {
+        TokensParameters::new(
             "fossy",
             "fossy",
-            &token_name,
-            TokenScope::Read,
+            "test",
+            token_scope,
             Utc::now()
                 .checked_add_signed(Duration::days(5))
                 .unwrap()
                 .naive_utc()
                 .date(),
-        );
+        )
+    }
+
+    #[test]
+    fn generate_read_token() {
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
-        let token = tokens(&fossology, &params).unwrap();
+        let token = tokens(&fossology, &params(TokenScope::Read)).unwrap();
 
         assert!(token.authorization.starts_with("Bearer"));
     }
 
     #[test]
     fn generate_write_token() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
-
-        let token_name = rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(10)
-            .map(char::from)
-            .collect::<String>();
-
-        let params = TokensParameters::new(
-            "fossy",
-            "fossy",
-            &token_name,
-            TokenScope::Write,
-            Utc::now()
-                .checked_add_signed(Duration::days(5))
-                .unwrap()
-                .naive_utc()
-                .date(),
-        );
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
-        let tokens = tokens(&fossology, &params).unwrap();
+        let tokens = tokens(&fossology, &params(TokenScope::Write)).unwrap();
 
         assert!(tokens.authorization.starts_with("Bearer"));
     }
@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Connection settings read from the command line, environment variables or a config file.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::CliError;
+
+/// Contents of the config file.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub uri: Option<String>,
+    pub token: Option<String>,
+}
+
+impl Config {
+    /// Read the config from `path`, or from the default location if `path` is not given.
+    ///
+    /// A missing config file is only an error if the path was given explicitly.
+    pub fn read(path: Option<&Path>) -> Result<Self, CliError> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        if !required && !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|err| CliError(format!("Can't read config {}: {}", path.display(), err)))?;
+
+        toml::from_str(&content)
+            .map_err(|err| CliError(format!("Invalid config {}: {}", path.display(), err)))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/fossology/config.toml`, falling back to `$HOME/.config`.
+fn default_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_home.join("fossology").join("config.toml"))
+}
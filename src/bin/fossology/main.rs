@@ -0,0 +1,683 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! # fossology
+//!
+//! Command-line interface for Fossology's REST API.
+
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    fmt,
+    fs::File,
+    path::{Path, PathBuf},
+    process, thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use clap::{Parser, Subcommand, ValueEnum};
+use fossology_rs::{
+    auth::{tokens, TokenScope, TokensParameters},
+    info::health,
+    job::{schedule_analysis, wait_for_jobs, Scancode, ScheduleAgents},
+    license::get_license,
+    report::{download_report, generate_report, ReportFormat},
+    upload::{
+        filesearch, new_upload_from_file, new_upload_from_file_deduplicated,
+        new_upload_from_server, new_upload_from_url, new_upload_from_vcs, Deduplicate, Hash,
+        NewUpload, ServerUpload, UrlUpload, VcsType, VcsUpload,
+    },
+    Fossology, FossologyError,
+};
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    output::{print, OutputFormat},
+};
+
+mod config;
+mod output;
+
+#[derive(Debug, Parser)]
+#[command(name = "fossology", version, about = "Interact with Fossology")]
+struct Cli {
+    /// Base URI of the API, e.g. `http://localhost:8080/repo/api/v1`.
+    #[arg(long, env = "FOSSOLOGY_URI", global = true)]
+    uri: Option<String>,
+
+    /// Access token for the API.
+    #[arg(long, env = "FOSSOLOGY_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+
+    /// Config file with `uri` and `token`. Defaults to `~/.config/fossology/config.toml`.
+    #[arg(long, env = "FOSSOLOGY_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Format of the output.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Create an access token.
+    Login {
+        #[arg(long)]
+        username: String,
+
+        #[arg(long, env = "FOSSOLOGY_PASSWORD", hide_env_values = true)]
+        password: String,
+
+        #[arg(long, default_value = "fossology-rs")]
+        token_name: String,
+
+        #[arg(long, value_enum, default_value_t = Scope::Read)]
+        scope: Scope,
+
+        /// Days until the token expires.
+        #[arg(long, default_value_t = 30)]
+        expire_days: i64,
+    },
+
+    /// Create a new upload.
+    Upload {
+        /// Folder for the upload.
+        #[arg(long, default_value_t = 1, global = true)]
+        folder: i32,
+
+        #[command(subcommand)]
+        source: UploadSource,
+    },
+
+    /// Schedule analysis agents for an upload.
+    Schedule {
+        upload_id: i32,
+
+        /// Folder of the upload.
+        #[arg(long, default_value_t = 1)]
+        folder: i32,
+
+        #[arg(long)]
+        group: Option<String>,
+
+        /// Agents to schedule.
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            default_value = "nomos,monk,ojo"
+        )]
+        agents: Vec<Agent>,
+
+        /// Deciders to schedule.
+        #[arg(long, value_enum, value_delimiter = ',')]
+        deciders: Vec<DeciderAgent>,
+    },
+
+    /// Wait until all jobs of an upload have finished.
+    Wait {
+        upload_id: i32,
+
+        /// Seconds between checking the status of the jobs.
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+
+        /// Give up after this many seconds.
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
+
+    /// Search for uploaded files by their hashes.
+    Filesearch {
+        /// Local files to hash and search for.
+        files: Vec<PathBuf>,
+
+        #[arg(long)]
+        sha256: Vec<String>,
+
+        #[arg(long)]
+        sha1: Vec<String>,
+
+        #[arg(long)]
+        md5: Vec<String>,
+
+        #[arg(long)]
+        group: Option<String>,
+    },
+
+    /// Generate and download a report for an upload.
+    Report {
+        upload_id: i32,
+
+        #[arg(long, value_enum, default_value_t = ReportType::Spdx2)]
+        report_format: ReportType,
+
+        /// File to write the report to.
+        #[arg(long)]
+        file: PathBuf,
+
+        #[arg(long)]
+        group: Option<String>,
+
+        /// Give up if the report is not ready after this many seconds.
+        #[arg(long, default_value_t = 600)]
+        timeout: u64,
+    },
+
+    /// Show a license.
+    License {
+        short_name: String,
+
+        #[arg(long)]
+        group: Option<String>,
+    },
+
+    /// Show the health of the Fossology instance.
+    Health,
+}
+
+#[derive(Debug, Subcommand)]
+enum UploadSource {
+    /// Upload a local file.
+    File {
+        path: PathBuf,
+
+        /// Return an existing upload of the same file instead of uploading it again.
+        #[arg(long, value_enum)]
+        deduplicate: Option<DeduplicateScope>,
+    },
+
+    /// Let Fossology download the file from a URL.
+    Url {
+        url: String,
+
+        /// Name of the upload.
+        #[arg(long)]
+        name: String,
+    },
+
+    /// Upload files from a path on the Fossology server.
+    Server {
+        /// Path of a file or directory on the server. With `--wildcard`, the directory whose
+        /// matching files are uploaded.
+        path: String,
+
+        /// Name of the upload.
+        #[arg(long)]
+        name: String,
+
+        /// Only upload the files in the directory matching the wildcard, like `*.tar.gz`.
+        #[arg(long)]
+        wildcard: Option<String>,
+    },
+
+    /// Upload the contents of a version control repository.
+    Vcs {
+        url: String,
+
+        #[arg(long, value_enum, default_value_t = Vcs::Git)]
+        vcs_type: Vcs,
+
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Name of the upload.
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DeduplicateScope {
+    /// Any upload the user can access.
+    Anywhere,
+
+    /// Only uploads in the folder of the upload.
+    Folder,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Scope {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Vcs {
+    Git,
+    Svn,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Agent {
+    Bucket,
+    Copyright,
+    Ecc,
+    Keyword,
+    Mime,
+    Monk,
+    Nomos,
+    Ojo,
+    Package,
+    Heritage,
+    Compatibility,
+    Ipra,
+    /// Scancode-toolkit scanning for licenses, copyrights, emails and urls.
+    Scancode,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DeciderAgent {
+    NomosMonk,
+    NewScanner,
+    Ojo,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ReportType {
+    Dep5,
+    Spdx2,
+    Spdx2Tv,
+    ReadmeOss,
+    UnifiedReport,
+    CliXml,
+}
+
+/// Error shown to the user.
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<FossologyError> for CliError {
+    fn from(err: FossologyError) -> Self {
+        Self(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        Self(err.to_string())
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(err) = run(cli) {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn run(cli: Cli) -> Result<(), CliError> {
+    let config = Config::read(cli.config.as_deref())?;
+    let uri = cli
+        .uri
+        .or(config.uri)
+        .ok_or_else(|| CliError("URI of the API is not set".to_string()))?;
+    let token = cli.token.or(config.token);
+    let output = cli.output;
+
+    let connect = || -> Result<Fossology, CliError> {
+        let token = token
+            .as_deref()
+            .ok_or_else(|| CliError("Access token is not set".to_string()))?;
+        Ok(Fossology::new(&uri, token)?)
+    };
+
+    // Creating tokens and checking health don't need an access token.
+    let connect_without_token = || -> Result<Fossology, CliError> {
+        Ok(Fossology::new(&uri, token.as_deref().unwrap_or_default())?)
+    };
+
+    match cli.command {
+        Command::Login {
+            username,
+            password,
+            token_name,
+            scope,
+            expire_days,
+        } => {
+            let fossology = connect_without_token()?;
+            let scope = match scope {
+                Scope::Read => TokenScope::Read,
+                Scope::Write => TokenScope::Write,
+            };
+            let expire = (Utc::now() + chrono::Duration::days(expire_days))
+                .naive_utc()
+                .date();
+            let params = TokensParameters::new(&username, &password, &token_name, scope, expire);
+            let token = tokens(&fossology, &params)?;
+            let rows = vec![vec![token.authorization.clone()]];
+            print(output, &token, &["AUTHORIZATION"], &rows)
+        }
+        Command::Upload { folder, source } => {
+            let fossology = connect()?;
+            let upload = upload(&fossology, folder, source)?;
+            let rows = vec![vec![
+                upload.upload_id.to_string(),
+                upload.existing.to_string(),
+            ]];
+            print(output, &upload, &["UPLOAD ID", "EXISTING"], &rows)
+        }
+        Command::Schedule {
+            upload_id,
+            folder,
+            group,
+            agents,
+            deciders,
+        } => {
+            let fossology = connect()?;
+            let schedule = schedule_agents(&agents, &deciders);
+            let job = schedule_analysis(&fossology, folder, upload_id, group, &schedule)?;
+            let rows = vec![vec![job.id.to_string()]];
+            print(output, &job, &["JOB ID"], &rows)
+        }
+        Command::Wait {
+            upload_id,
+            interval,
+            timeout,
+        } => {
+            let fossology = connect()?;
+            wait(
+                &fossology,
+                upload_id,
+                Duration::from_secs(interval),
+                timeout.map(Duration::from_secs),
+                output,
+            )
+        }
+        Command::Filesearch {
+            files,
+            sha256,
+            sha1,
+            md5,
+            group,
+        } => {
+            let fossology = connect()?;
+            let mut hashes = files
+                .iter()
+                .map(Hash::from_file)
+                .collect::<Result<Vec<_>, _>>()?;
+            hashes.extend(sha256.iter().map(|hash| Hash::from_sha256(hash)));
+            hashes.extend(sha1.iter().map(|hash| Hash::from_sha1(hash)));
+            hashes.extend(md5.iter().map(|hash| Hash::from_md5(hash)));
+
+            search_hashes(&fossology, &hashes, group, output)
+        }
+        Command::Report {
+            upload_id,
+            report_format,
+            file,
+            group,
+            timeout,
+        } => {
+            let fossology = connect()?;
+            report(
+                &fossology,
+                upload_id,
+                report_format,
+                &file,
+                group.as_deref(),
+                Duration::from_secs(timeout),
+                output,
+            )
+        }
+        Command::License { short_name, group } => {
+            let fossology = connect()?;
+            let license = get_license(&fossology, &short_name, group.as_deref())?;
+            let rows = vec![vec![
+                license.short_name.clone(),
+                license.full_name.clone(),
+                license.risk.map(|r| r.to_string()).unwrap_or_default(),
+            ]];
+            print(
+                output,
+                &license,
+                &["SHORT NAME", "FULL NAME", "RISK"],
+                &rows,
+            )
+        }
+        Command::Health => {
+            let fossology = connect_without_token()?;
+            let health = health(&fossology)?;
+            let rows = vec![
+                vec!["fossology".to_string(), health.status.clone()],
+                vec!["scheduler".to_string(), health.scheduler.status.clone()],
+                vec!["db".to_string(), health.db.status.clone()],
+            ];
+            print(output, &health, &["COMPONENT", "STATUS"], &rows)
+        }
+    }
+}
+
+fn upload(
+    fossology: &Fossology,
+    folder: i32,
+    source: UploadSource,
+) -> Result<NewUpload, FossologyError> {
+    match source {
+        UploadSource::File {
+            path,
+            deduplicate: None,
+        } => new_upload_from_file(fossology, folder, path),
+        UploadSource::File {
+            path,
+            deduplicate: Some(scope),
+        } => {
+            let deduplicate = match scope {
+                DeduplicateScope::Anywhere => Deduplicate::Anywhere,
+                DeduplicateScope::Folder => Deduplicate::InFolder,
+            };
+            new_upload_from_file_deduplicated(fossology, folder, path, deduplicate)
+        }
+        UploadSource::Url { url, name } => {
+            new_upload_from_url(fossology, folder, &UrlUpload::new(&url, &name))
+        }
+        UploadSource::Server {
+            path,
+            name,
+            wildcard,
+        } => {
+            let server_upload = wildcard.map_or_else(
+                || ServerUpload::new(&path, &name),
+                |wildcard| ServerUpload::with_wildcard(&path, &wildcard, &name),
+            );
+            new_upload_from_server(fossology, folder, &server_upload)
+        }
+        UploadSource::Vcs {
+            url,
+            vcs_type,
+            branch,
+            name,
+        } => {
+            let vcs_type = match vcs_type {
+                Vcs::Git => VcsType::Git,
+                Vcs::Svn => VcsType::Svn,
+            };
+            let mut vcs_upload = VcsUpload::new(vcs_type, &url);
+            vcs_upload.vcs_branch = branch;
+            vcs_upload.vcs_name = name;
+            new_upload_from_vcs(fossology, folder, &vcs_upload)
+        }
+    }
+}
+
+fn search_hashes(
+    fossology: &Fossology,
+    hashes: &[Hash],
+    group: Option<String>,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let results = filesearch(fossology, hashes, group)?;
+    let rows = results
+        .iter()
+        .map(|result| {
+            let findings = result.findings.as_ref();
+            vec![
+                display_hash(&result.hash),
+                join(&result.uploads),
+                findings.map(|f| f.scanner.join(", ")).unwrap_or_default(),
+                findings
+                    .map(|f| f.conclusion.join(", "))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    print(
+        output,
+        &results,
+        &["HASH", "UPLOADS", "SCANNER", "CONCLUSION"],
+        &rows,
+    )
+}
+
+fn schedule_agents(agents: &[Agent], deciders: &[DeciderAgent]) -> ScheduleAgents {
+    let mut schedule = ScheduleAgents::default();
+
+    for agent in agents {
+        match agent {
+            Agent::Bucket => schedule.analysis.bucket = true,
+            Agent::Copyright => schedule.analysis.copyright_email_author = true,
+            Agent::Ecc => schedule.analysis.ecc = true,
+            Agent::Keyword => schedule.analysis.keyword = true,
+            Agent::Mime => schedule.analysis.mime = true,
+            Agent::Monk => schedule.analysis.monk = true,
+            Agent::Nomos => schedule.analysis.nomos = true,
+            Agent::Ojo => schedule.analysis.ojo = true,
+            Agent::Package => schedule.analysis.package = true,
+            Agent::Heritage => schedule.analysis.heritage = true,
+            Agent::Compatibility => schedule.analysis.compatibility = true,
+            Agent::Ipra => schedule.analysis.ipra = true,
+            Agent::Scancode => {
+                schedule.analysis.scancode = Some(Scancode {
+                    license: true,
+                    copyright: true,
+                    email: true,
+                    url: true,
+                });
+            }
+        }
+    }
+
+    for decider in deciders {
+        match decider {
+            DeciderAgent::NomosMonk => schedule.decider.nomos_monk = true,
+            DeciderAgent::NewScanner => schedule.decider.new_scanner = true,
+            DeciderAgent::Ojo => schedule.decider.ojo_decider = true,
+        }
+    }
+
+    schedule
+}
+
+/// Wait for the jobs of the upload and print them.
+fn wait(
+    fossology: &Fossology,
+    upload_id: i32,
+    interval: Duration,
+    timeout: Option<Duration>,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let jobs = wait_for_jobs(fossology, upload_id, &[], interval, timeout)?;
+
+    let rows = jobs
+        .iter()
+        .map(|job| {
+            vec![
+                job.id.to_string(),
+                job.name.clone(),
+                format!("{:?}", job.status),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print(output, &jobs, &["JOB ID", "NAME", "STATUS"], &rows)
+}
+
+/// Generate the report and download it once it's ready.
+fn report(
+    fossology: &Fossology,
+    upload_id: i32,
+    report_format: ReportType,
+    path: &Path,
+    group: Option<&str>,
+    timeout: Duration,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let report_format = match report_format {
+        ReportType::Dep5 => ReportFormat::Dep5,
+        ReportType::Spdx2 => ReportFormat::Spdx2,
+        ReportType::Spdx2Tv => ReportFormat::Spdx2Tv,
+        ReportType::ReadmeOss => ReportFormat::ReadmeOss,
+        ReportType::UnifiedReport => ReportFormat::UnifiedReport,
+        ReportType::CliXml => ReportFormat::CliXml,
+    };
+
+    let report_id = generate_report(fossology, upload_id, report_format, group)?;
+
+    let started = Instant::now();
+    let mut file = File::create(path)?;
+    let size = loop {
+        if let Some(size) = download_report(fossology, report_id, &mut file)? {
+            break size;
+        }
+        if started.elapsed() >= timeout {
+            return Err(CliError(format!(
+                "Report {} was not ready in time",
+                report_id
+            )));
+        }
+        thread::sleep(Duration::from_secs(2));
+    };
+
+    let downloaded = DownloadedReport {
+        report_id,
+        path: path.to_path_buf(),
+        size,
+    };
+    let rows = vec![vec![
+        report_id.to_string(),
+        path.display().to_string(),
+        size.to_string(),
+    ]];
+    print(output, &downloaded, &["REPORT ID", "PATH", "BYTES"], &rows)
+}
+
+#[derive(Debug, Serialize)]
+struct DownloadedReport {
+    report_id: i32,
+    path: PathBuf,
+    size: u64,
+}
+
+/// The most specific hash available.
+fn display_hash(hash: &Hash) -> String {
+    hash.sha256
+        .as_ref()
+        .or(hash.sha1.as_ref())
+        .or(hash.md5.as_ref())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn join(values: &[i32]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
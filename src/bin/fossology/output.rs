@@ -0,0 +1,97 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Printing the results as JSON or as tables.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::CliError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table.
+    Table,
+    /// Pretty-printed JSON.
+    Json,
+}
+
+/// Print `value` as JSON, or `rows` as a table with the given headers.
+pub fn print<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    value: &T,
+    headers: &[&str],
+    rows: &[Vec<String>],
+) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|err| CliError(format!("Can't serialize output: {}", err)))?;
+            println!("{}", json);
+        }
+        OutputFormat::Table => print!("{}", table(headers, rows)),
+    }
+
+    Ok(())
+}
+
+/// Format rows as columns aligned to the widest value.
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut output = format_row(headers.to_vec());
+    for row in rows {
+        output.push_str(&format_row(row.iter().map(String::as_str).collect()));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn columns_are_aligned() {
+        let rows = vec![
+            vec!["1".to_string(), "nomos".to_string()],
+            vec!["1234".to_string(), "ojo".to_string()],
+        ];
+
+        assert_eq!(
+            table(&["ID", "NAME"], &rows),
+            "ID    NAME\n1     nomos\n1234  ojo\n"
+        );
+    }
+
+    #[test]
+    fn non_ascii_headers_are_aligned_by_characters() {
+        let rows = vec![vec!["1".to_string(), "x".to_string()]];
+
+        assert_eq!(
+            table(&["ÄÖ", "B"], &rows),
+            "ÄÖ  B
+1   x
+"
+        );
+    }
+}
@@ -0,0 +1,636 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Record interactions with the API and replay them without a server.
+//!
+//! A client created with [`Fossology::recording`](crate::Fossology::recording) sends the requests
+//! to the API and writes every request and response to a JSON cassette file. A client created with
+//! [`Fossology::replaying`](crate::Fossology::replaying) responds to the same requests from the
+//! cassette, so code using the crate can be tested without a running Fossology instance.
+//!
+//! The interactions are kept in memory and the cassette is written when the recording client is
+//! dropped, or earlier with [`Recorder::finish`].
+//!
+//! Bodies of responses that are not JSON, like downloaded files, are streamed to separate files
+//! next to the cassette as they are read, so the downloads are not held in memory.
+//!
+//! [`Recorder`] and [`Replayer`] are [`Transport`]s, so they can also be combined with other
+//! transports with [`Fossology::with_transport`](crate::Fossology::with_transport).
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use log::warn;
+use reqwest::{
+    blocking::{Request, Response},
+    header::{HeaderMap, CONTENT_TYPE},
+    Body, Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{transport::Transport, FossologyError};
+
+/// Headers that are not recorded. Authorization contains the access token and the others vary
+/// between otherwise identical requests.
+const IGNORED_HEADERS: [&str; 4] = [
+    "authorization",
+    "content-length",
+    "content-type",
+    "transfer-encoding",
+];
+
+/// Base path of the API used by cassettes that don't record it.
+const DEFAULT_BASE_PATH: &str = "/repo/api/v1";
+
+/// Size of the chunks streamed bodies are read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Recorded interactions with the API.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Path of the API on the server, like `/repo/api/v2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_path: Option<String>,
+
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// # Errors
+    ///
+    /// - File can't be opened.
+    /// - File is not a valid cassette.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FossologyError> {
+        let reader = BufReader::new(File::open(path)?);
+
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// # Errors
+    ///
+    /// - File can't be created.
+    /// - Cassette can't be serialized.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FossologyError> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+}
+
+/// Request and the response received for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+
+    /// Path and query relative to the base uri of the API.
+    pub path: String,
+
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Body of the request. Not recorded for streamed bodies, like uploaded files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl RecordedRequest {
+    fn from_request(base_uri: &str, request: &Request) -> Self {
+        let url = request.url().as_str();
+        let path = url
+            .strip_prefix(base_uri)
+            .unwrap_or(url)
+            .trim_start_matches('/')
+            .to_string();
+
+        Self {
+            method: request.method().to_string(),
+            path,
+            headers: recorded_headers(request.headers()),
+            body: request
+                .body()
+                .and_then(reqwest::blocking::Body::as_bytes)
+                .map(|body| String::from_utf8_lossy(body).to_string()),
+        }
+    }
+
+    /// Bodies are only compared if both of the requests have one.
+    fn matches(&self, other: &Self) -> bool {
+        self.method == other.method
+            && self.path == other.path
+            && self.headers == other.headers
+            && match (&self.body, &other.body) {
+                (Some(body), Some(other)) => body == other,
+                _ => true,
+            }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    pub body: RecordedBody,
+}
+
+/// Body of a response. Bodies that are not valid UTF-8 are stored as hex.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordedBody {
+    Text(String),
+    Hex(String),
+
+    /// Name of the file next to the cassette that contains the body.
+    File(String),
+}
+
+impl RecordedBody {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Hex(hex::encode(bytes)),
+        }
+    }
+
+    /// Body files are looked up from `directory`.
+    fn to_body(&self, directory: &Path) -> Result<Body, FossologyError> {
+        match self {
+            Self::Text(text) => Ok(Body::from(text.clone())),
+            Self::Hex(hex) => hex::decode(hex)
+                .map(Body::from)
+                .map_err(|err| FossologyError::Other(format!("Invalid recorded body: {}", err))),
+            Self::File(name) => {
+                let file = File::open(directory.join(name))?;
+
+                Ok(Body::wrap_stream(ReaderStream::new(file, None)))
+            }
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn to_response(&self, directory: &Path) -> Result<Response, FossologyError> {
+        build_response(self.status, &self.headers, self.body.to_body(directory)?)
+    }
+}
+
+fn build_response(
+    status: u16,
+    headers: &BTreeMap<String, String>,
+    body: Body,
+) -> Result<Response, FossologyError> {
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+
+    builder
+        .body(body)
+        .map(Response::from)
+        .map_err(|err| FossologyError::Other(format!("Invalid recorded response: {}", err)))
+}
+
+/// Body that is read from `reader` in chunks as the response is read. The chunks are also
+/// written to `copy`, if there is one.
+struct ReaderStream<R> {
+    reader: Mutex<R>,
+    copy: Option<Mutex<BufWriter<File>>>,
+}
+
+impl<R: Read> ReaderStream<R> {
+    fn new(reader: R, copy: Option<File>) -> Self {
+        Self {
+            reader: Mutex::new(reader),
+            copy: copy.map(|file| Mutex::new(BufWriter::new(file))),
+        }
+    }
+
+    fn read_chunk(&self) -> std::io::Result<Option<Vec<u8>>> {
+        let mut reader = self.reader.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut chunk = vec![0; CHUNK_SIZE];
+
+        let read = loop {
+            match reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        };
+        chunk.truncate(read);
+
+        if let Some(copy) = &self.copy {
+            let mut copy = copy.lock().unwrap_or_else(PoisonError::into_inner);
+            copy.write_all(&chunk)?;
+            if read == 0 {
+                copy.flush()?;
+            }
+        }
+
+        Ok(if read == 0 { None } else { Some(chunk) })
+    }
+}
+
+impl<R: Read + Unpin> Stream for ReaderStream<R> {
+    type Item = std::io::Result<Vec<u8>>;
+
+    /// Reads the next chunk. Blocks, because the stream is only polled while a blocking
+    /// [`Response`] is being read.
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.read_chunk().transpose())
+    }
+}
+
+/// True for responses whose body is JSON, which is recorded in the cassette itself.
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"))
+}
+
+fn recorded_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !IGNORED_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// Sends the requests with another transport and writes the interactions to a cassette.
+///
+/// The cassette is written when the recorder is dropped. Errors are only logged then, so call
+/// [`Recorder::finish`] to handle them.
+#[derive(Debug)]
+pub struct Recorder {
+    inner: Box<dyn Transport>,
+    base_uri: String,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+
+    /// True if interactions have been recorded since the cassette was written.
+    unsaved: AtomicBool,
+
+    /// Number of bodies streamed to files, used to name the files.
+    body_files: AtomicUsize,
+}
+
+impl Recorder {
+    /// Starts a new cassette at `path` for requests to the API at `base_uri`. Existing file is
+    /// overwritten.
+    ///
+    /// # Errors
+    ///
+    /// - `base_uri` is not a valid URL.
+    /// - Cassette can't be written.
+    pub fn new<T: Transport + 'static, P: AsRef<Path>>(
+        inner: T,
+        base_uri: &str,
+        path: P,
+    ) -> Result<Self, FossologyError> {
+        let base_path = Url::parse(base_uri)
+            .map_err(|err| FossologyError::Other(format!("Invalid uri {}: {}", base_uri, err)))?
+            .path()
+            .trim_end_matches('/')
+            .to_string();
+        let cassette = Cassette {
+            base_path: Some(base_path),
+            interactions: Vec::new(),
+        };
+        cassette.save(&path)?;
+
+        Ok(Self {
+            inner: Box::new(inner),
+            base_uri: base_uri.to_owned(),
+            path: path.as_ref().to_path_buf(),
+            cassette: Mutex::new(cassette),
+            unsaved: AtomicBool::new(false),
+            body_files: AtomicUsize::new(0),
+        })
+    }
+
+    /// Writes the interactions recorded so far to the cassette.
+    ///
+    /// # Errors
+    ///
+    /// - Cassette can't be written.
+    pub fn finish(&self) -> Result<(), FossologyError> {
+        let cassette = self.cassette.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.unsaved.swap(false, Ordering::SeqCst) {
+            if let Err(err) = cassette.save(&self.path) {
+                self.unsaved.store(true, Ordering::SeqCst);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates the file for the next streamed body next to the cassette. Returns the name of the
+    /// file and the file.
+    fn create_body_file(&self) -> Result<(String, File), FossologyError> {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = format!(
+            "{}-{}.body",
+            stem,
+            self.body_files.fetch_add(1, Ordering::SeqCst)
+        );
+        let file = File::create(self.path.with_file_name(&name))?;
+
+        Ok((name, file))
+    }
+}
+
+impl Transport for Recorder {
+    fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+        let recorded_request = RecordedRequest::from_request(&self.base_uri, &request);
+
+        let response = self.inner.execute(request)?;
+        let status = response.status().as_u16();
+        let headers = recorded_headers(response.headers());
+
+        let (recorded_body, body) = if is_json(response.headers()) {
+            let bytes = response.bytes()?;
+            (RecordedBody::from_bytes(&bytes), Body::from(bytes))
+        } else {
+            let (name, file) = self.create_body_file()?;
+            let stream = ReaderStream::new(response, Some(file));
+            (RecordedBody::File(name), Body::wrap_stream(stream))
+        };
+
+        let mut cassette = self.cassette.lock().unwrap_or_else(PoisonError::into_inner);
+        cassette.interactions.push(Interaction {
+            request: recorded_request,
+            response: RecordedResponse {
+                status,
+                headers: headers.clone(),
+                body: recorded_body,
+            },
+        });
+        self.unsaved.store(true, Ordering::SeqCst);
+        drop(cassette);
+
+        build_response(status, &headers, body)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            warn!("Cassette {} can't be written: {}", self.path.display(), err);
+        }
+    }
+}
+
+/// Responds to the requests from the interactions in a cassette.
+#[derive(Debug)]
+pub struct Replayer {
+    base_uri: String,
+
+    /// Directory of the cassette, which contains the files of streamed bodies.
+    directory: PathBuf,
+
+    /// Interactions that have not been replayed yet.
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    /// Loads the cassette at `path` for replaying requests to the API at `host`, which is
+    /// followed by the base path of the recorded API.
+    ///
+    /// # Errors
+    ///
+    /// - Cassette can't be read.
+    pub fn new<P: AsRef<Path>>(host: &str, path: P) -> Result<Self, FossologyError> {
+        let cassette = Cassette::load(&path)?;
+        let base_path = cassette.base_path.as_deref().unwrap_or(DEFAULT_BASE_PATH);
+
+        Ok(Self {
+            base_uri: format!("{}{}", host.trim_end_matches('/'), base_path),
+            directory: path
+                .as_ref()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            interactions: Mutex::new(cassette.interactions.into_iter().map(Some).collect()),
+        })
+    }
+
+    /// Uri of the API the requests are replayed for.
+    pub fn base_uri(&self) -> &str {
+        &self.base_uri
+    }
+}
+
+impl Transport for Replayer {
+    fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+        let recorded_request = RecordedRequest::from_request(&self.base_uri, &request);
+
+        let mut interactions = self
+            .interactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let interaction = interactions
+            .iter_mut()
+            .find(|interaction| {
+                interaction
+                    .as_ref()
+                    .is_some_and(|i| i.request.matches(&recorded_request))
+            })
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                FossologyError::Other(format!(
+                    "No recorded response for {} {}",
+                    recorded_request.method, recorded_request.path
+                ))
+            })?;
+        drop(interactions);
+
+        interaction.response.to_response(&self.directory)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, sync::Arc};
+
+    use rand::{distributions::Alphanumeric, Rng};
+
+    use crate::{
+        job::{get_jobs, JobStatus},
+        license::get_license,
+        testing::MockFossology,
+        transport::HttpTransport,
+        upload::{download_upload, filesearch, new_upload_from_file, Hash},
+        Fossology,
+    };
+
+    use super::*;
+
+    fn cassette_path() -> PathBuf {
+        let name = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect::<String>();
+
+        env::temp_dir().join(format!("fossology-rs-{}.json", name))
+    }
+
+    /// Remove the cassette and the files of its streamed bodies.
+    fn remove_cassette(path: &Path) {
+        for interaction in Cassette::load(path).unwrap().interactions {
+            if let RecordedBody::File(name) = interaction.response.body {
+                std::fs::remove_file(path.with_file_name(name)).unwrap();
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn recorded_interactions_are_replayed() {
+        let path = cassette_path();
+        let hash = Hash::from_file("tests/data/base-files_11.tar.xz").unwrap();
+
+        let upload_id = {
+            let mock = MockFossology::start();
+            mock.set_processing_polls(1);
+            let fossology = Fossology::recording(&mock.uri(), mock.token(), &path).unwrap();
+
+            let upload =
+                new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+            get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+            get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+            filesearch(&fossology, std::slice::from_ref(&hash), None).unwrap();
+            download_upload(&fossology, upload.upload_id, &mut Vec::new()).unwrap();
+            get_license(&fossology, "MIT", None).unwrap();
+
+            upload.upload_id
+        };
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert!(cassette
+            .interactions
+            .iter()
+            .all(|i| !i.request.headers.contains_key("authorization")));
+        assert!(cassette
+            .interactions
+            .iter()
+            .any(|i| matches!(i.response.body, RecordedBody::File(_))));
+
+        let fossology = Fossology::replaying(&path).unwrap();
+        assert!(fossology.uri.ends_with("/repo/api/v2"));
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        assert_eq!(upload.upload_id, upload_id);
+
+        let jobs = get_jobs(&fossology, Some(upload_id), None, None, None).unwrap();
+        assert_eq!(jobs[0].status, JobStatus::Processing);
+        let jobs = get_jobs(&fossology, Some(upload_id), None, None, None).unwrap();
+        assert_eq!(jobs[0].status, JobStatus::Completed);
+
+        let results = filesearch(&fossology, &[hash], None).unwrap();
+        assert_eq!(results[0].uploads, vec![upload_id]);
+
+        let mut content = Vec::new();
+        download_upload(&fossology, upload_id, &mut content).unwrap();
+        assert_eq!(
+            content,
+            std::fs::read("tests/data/base-files_11.tar.xz").unwrap()
+        );
+
+        assert_eq!(
+            get_license(&fossology, "MIT", None).unwrap().full_name,
+            "MIT License"
+        );
+
+        remove_cassette(&path);
+    }
+
+    #[test]
+    fn api_v1_is_replayed_with_its_base_path() {
+        let path = cassette_path();
+
+        {
+            let mock = MockFossology::with_version("1.6.0");
+            let fossology = Fossology::recording(&mock.uri(), mock.token(), &path).unwrap();
+            get_jobs(&fossology, None, None, None, None).unwrap();
+        }
+
+        assert_eq!(
+            Cassette::load(&path).unwrap().base_path.as_deref(),
+            Some("/repo/api/v1")
+        );
+
+        let fossology = Fossology::replaying(&path).unwrap();
+        assert!(fossology.uri.ends_with("/repo/api/v1"));
+        assert_eq!(fossology.api_version().to_string(), "1.6.0");
+        assert!(get_jobs(&fossology, None, None, None, None).is_ok());
+
+        remove_cassette(&path);
+    }
+
+    #[test]
+    fn cassette_is_written_when_finished() {
+        let path = cassette_path();
+        let mock = MockFossology::start();
+        let recorder =
+            Arc::new(Recorder::new(HttpTransport::new().unwrap(), &mock.uri(), &path).unwrap());
+        let fossology =
+            Fossology::with_transport(&mock.uri(), mock.token(), Arc::clone(&recorder)).unwrap();
+
+        get_jobs(&fossology, None, None, None, None).unwrap();
+        assert!(Cassette::load(&path).unwrap().interactions.is_empty());
+
+        recorder.finish().unwrap();
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 2);
+
+        get_jobs(&fossology, None, None, None, None).unwrap();
+        drop(fossology);
+        drop(recorder);
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 3);
+
+        remove_cassette(&path);
+    }
+
+    #[test]
+    fn unrecorded_request_fails() {
+        let path = cassette_path();
+
+        {
+            let mock = MockFossology::start();
+            Fossology::recording(&mock.uri(), mock.token(), &path).unwrap();
+        }
+
+        let fossology = Fossology::replaying(&path).unwrap();
+
+        assert!(get_jobs(&fossology, None, None, None, None).is_err());
+
+        remove_cassette(&path);
+    }
+}
@@ -4,9 +4,9 @@
 
 //! Info about the API.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{Fossology, FossologyError, FossologyResponse};
+use crate::{version::Capability, Fossology, FossologyError, FossologyResponse};
 
 /// # Errors
 ///
@@ -14,9 +14,7 @@ use crate::{Fossology, FossologyError, FossologyResponse};
 /// - Response can't be serialized to [`ApiInformation`] or [`Info`](crate::Info).
 /// - Response is not [`ApiInformation`].
 pub fn info(fossology: &Fossology) -> Result<ApiInformation, FossologyError> {
-    if !fossology.version_is_at_least("1.3.3")? {
-        return Err(FossologyError::UnsupportedVersion);
-    };
+    fossology.require(Capability::ApiInfo)?;
 
     let response: FossologyResponse<ApiInformation> =
         fossology.init_get_with_token("info").send()?.json()?;
@@ -64,44 +62,37 @@ pub struct ApiLicense {
 /// - Response can't be serialized to [`Health`] or [`Info`](crate::Info).
 /// - Response is not [`Health`].
 pub fn health(fossology: &Fossology) -> Result<Health, FossologyError> {
-    if !fossology.version_is_at_least("1.3.3")? {
-        return Err(FossologyError::UnsupportedVersion);
-    };
-
-    let response: FossologyResponse<Health> = fossology
-        .client
-        .get(&format!("{}/health", fossology.uri))
-        .send()?
-        .json()?;
+    fossology.require(Capability::Health)?;
+
+    let response: FossologyResponse<Health> = fossology.init_get("health").send()?.json()?;
     match response {
         FossologyResponse::Response(res) => Ok(res),
         FossologyResponse::ApiError(res) => Err(FossologyError::Other(res.message)),
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Health {
     pub status: String,
     pub scheduler: Status,
     pub db: Status,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Status {
     pub status: String,
 }
 
 #[cfg(test)]
 mod test {
+    use crate::{testing::MockFossology, version::ApiVersion};
+
     use super::*;
 
     #[test]
     fn api_information() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
-
-        if !fossology.version_is_at_least("1.3.3").unwrap() {
-            return;
-        };
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
         let info = info(&fossology).unwrap();
         assert_eq!(info.name, "FOSSology API");
@@ -109,7 +100,8 @@ mod test {
 
     #[test]
     fn old_version() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
         let info = version(&fossology).unwrap();
 
@@ -118,11 +110,8 @@ mod test {
 
     #[test]
     fn get_health() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
-
-        if !fossology.version_is_at_least("1.3.3").unwrap() {
-            return;
-        };
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
         let health = health(&fossology).unwrap();
 
@@ -130,4 +119,37 @@ mod test {
         assert_eq!(health.scheduler.status, "OK");
         assert_eq!(health.db.status, "OK");
     }
+
+    #[test]
+    fn info_and_health_are_available_from_1_3_3() {
+        let mock = MockFossology::with_version("1.3.3");
+        let fossology = mock.fossology();
+
+        assert_eq!(info(&fossology).unwrap().version, "1.3.3");
+        assert_eq!(health(&fossology).unwrap().status, "OK");
+        assert_eq!(version(&fossology).unwrap().version, "1.3.3");
+    }
+
+    #[test]
+    fn info_and_health_are_unsupported_before_1_3_3() {
+        let mock = MockFossology::with_version("1.3.2");
+        let fossology = mock.fossology();
+
+        assert!(matches!(
+            info(&fossology),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::ApiInfo,
+                ..
+            })
+        ));
+        assert!(matches!(
+            health(&fossology),
+            Err(FossologyError::UnsupportedVersion {
+                required,
+                actual,
+                ..
+            }) if required == ApiVersion::new(1, 3, 3) && actual == ApiVersion::new(1, 3, 2)
+        ));
+        assert_eq!(version(&fossology).unwrap().version, "1.3.2");
+    }
 }
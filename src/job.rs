@@ -4,9 +4,25 @@
 
 //! Create and get jobs.
 
+use std::{
+    collections::BTreeMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    info_response, total_pages,
+    utilities::{deserialize_date_time, deserialize_id, deserialize_optional_date_time},
+    version::Capability,
+    Fossology, FossologyError, FossologyResponse, InfoWithNumber, Page, Paginator,
+};
 
-use crate::{Fossology, FossologyError, FossologyResponse, InfoWithNumber};
+pub mod reuse;
 
 /// # Errors
 ///
@@ -23,25 +39,25 @@ pub fn get_jobs(
     let mut builder = fossology.init_get_with_token("jobs");
 
     builder = if let Some(upload_id) = upload_id {
-        builder.query(&[("upload", &upload_id.to_string())])
+        builder.query("upload", upload_id)
     } else {
         builder
     };
 
     builder = if let Some(group_name) = group_name {
-        builder.header("groupName", group_name)
+        builder.param("groupName", group_name)
     } else {
         builder
     };
 
     builder = if let Some(limit) = limit {
-        builder.header("limit", limit)
+        builder.param("limit", limit)
     } else {
         builder
     };
 
     builder = if let Some(page) = page {
-        builder.header("page", page)
+        builder.param("page", page)
     } else {
         builder
     };
@@ -54,11 +70,306 @@ pub fn get_jobs(
     }
 }
 
+/// Kill the job, or only the agent of the job with the queue id. Killed jobs can't be resumed.
+///
+/// # Errors
+///
+/// - Job control is not supported by the API version.
+/// - Token doesn't have the permission to kill the job.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Job or queue entry doesn't exist.
+pub fn kill_job(
+    fossology: &Fossology,
+    job_id: i32,
+    queue_id: Option<i32>,
+) -> Result<(), FossologyError> {
+    fossology.require(Capability::JobControl)?;
+
+    let path = match queue_id {
+        Some(queue_id) => format!("jobs/{}/{}", job_id, queue_id),
+        None => format!("jobs/{}", job_id),
+    };
+
+    info_response(fossology.init_delete_with_token(&path).send()?)?;
+
+    Ok(())
+}
+
+/// Pause the job. The agents that are running finish, but no new agents are started.
+///
+/// # Errors
+///
+/// - Job control is not supported by the API version.
+/// - Token doesn't have the permission to change the job.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Job doesn't exist.
+pub fn pause_job(fossology: &Fossology, job_id: i32) -> Result<(), FossologyError> {
+    change_job(fossology, job_id, &JobChange::Pause)
+}
+
+/// Resume the paused job.
+///
+/// # Errors
+///
+/// - Job control is not supported by the API version.
+/// - Token doesn't have the permission to change the job.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Job doesn't exist or is not paused.
+pub fn resume_job(fossology: &Fossology, job_id: i32) -> Result<(), FossologyError> {
+    change_job(fossology, job_id, &JobChange::Resume)
+}
+
+/// Change the priority of the job in the queue. Jobs with higher priority are run first.
+///
+/// # Errors
+///
+/// - Job control is not supported by the API version.
+/// - Token doesn't have the permission to change the job.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Job doesn't exist.
+pub fn set_job_priority(
+    fossology: &Fossology,
+    job_id: i32,
+    priority: i32,
+) -> Result<(), FossologyError> {
+    change_job(fossology, job_id, &JobChange::Priority { priority })
+}
+
+fn change_job(
+    fossology: &Fossology,
+    job_id: i32,
+    change: &JobChange,
+) -> Result<(), FossologyError> {
+    fossology.require(Capability::JobControl)?;
+
+    let response = fossology
+        .init_patch_with_token(&format!("jobs/{}", job_id))
+        .json(change)
+        .send()?;
+    info_response(response)?;
+
+    Ok(())
+}
+
+/// Change to a job with `PATCH /jobs/{id}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum JobChange {
+    Pause,
+    Resume,
+    Priority { priority: i32 },
+}
+
+/// Get the job by its id. Returns `None` if the job doesn't exist.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Job`] or [`Info`](crate::Info).
+/// - Response is not [`Job`].
+pub fn get_job_by_id(fossology: &Fossology, job_id: i32) -> Result<Option<Job>, FossologyError> {
+    let response = fossology
+        .init_get_with_token(&format!("jobs/{}", job_id))
+        .send()?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    match response.json::<FossologyResponse<Job>>()? {
+        FossologyResponse::Response(res) => Ok(Some(res)),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Get one page of the jobs matching the filter. The page can contain fewer jobs than `limit`,
+/// see [`Page`].
+///
+/// # Errors
+///
+/// - Listing the jobs of all users is not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Vec`] of [`Job`]s or [`Info`](crate::Info).
+/// - Response is not [`Vec`] of [`Job`]s.
+pub fn list_jobs(
+    fossology: &Fossology,
+    filter: &JobFilter,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<JobPage, FossologyError> {
+    let mut page = get_jobs_page(fossology, filter, limit, page)?;
+    page.items.retain(|job| filter.matches(job));
+
+    Ok(page)
+}
+
+/// Iterate over all jobs matching the filter, retrieving the jobs page by page.
+pub fn iter_jobs<'a>(fossology: &'a Fossology, filter: &'a JobFilter) -> JobIter<'a> {
+    Paginator::new(move |limit, page| get_jobs_page(fossology, filter, Some(limit), Some(page)))
+        .retain(move |job| filter.matches(job))
+}
+
+/// Poll the jobs of the upload until all of them are finished, and return all jobs of the
+/// upload.
+///
+/// The jobs with ids in `ignored_jobs` are not waited for and their failures are not reported.
+/// Collect the ids of the existing jobs before scheduling new jobs to wait only for the new jobs.
+///
+/// # Errors
+///
+/// - Jobs can't be retrieved.
+/// - Jobs are still running after `timeout`, returned as [`FossologyError::Timeout`].
+/// - Any of the jobs failed, returned as [`FossologyError::JobFailed`].
+pub fn wait_for_jobs(
+    fossology: &Fossology,
+    upload_id: i32,
+    ignored_jobs: &[i32],
+    interval: Duration,
+    timeout: Option<Duration>,
+) -> Result<Vec<Job>, FossologyError> {
+    let started = Instant::now();
+    let filter = JobFilter {
+        upload_id: Some(upload_id),
+        ..JobFilter::default()
+    };
+    let is_waited = |job: &&Job| !ignored_jobs.contains(&job.id);
+
+    let jobs = loop {
+        let jobs = iter_jobs(fossology, &filter).collect::<Result<Vec<_>, _>>()?;
+
+        if jobs
+            .iter()
+            .filter(is_waited)
+            .all(|job| job.status.is_finished())
+        {
+            break jobs;
+        }
+
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            return Err(FossologyError::Timeout(format!(
+                "Jobs of upload {} did not finish in time",
+                upload_id
+            )));
+        }
+
+        thread::sleep(interval);
+    };
+
+    if jobs
+        .iter()
+        .filter(is_waited)
+        .any(|job| job.status == JobStatus::Failed)
+    {
+        return Err(FossologyError::JobFailed {
+            upload_id,
+            agents: jobs
+                .iter()
+                .filter(is_waited)
+                .flat_map(Job::failed_agents)
+                .map(|queue| format!("{} ({})", queue.job_queue_type, queue.status))
+                .collect(),
+        });
+    }
+
+    Ok(jobs)
+}
+
+/// Page of jobs before filtering the received jobs.
+fn get_jobs_page(
+    fossology: &Fossology,
+    filter: &JobFilter,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<JobPage, FossologyError> {
+    let mut builder = if filter.all_users {
+        fossology.require(Capability::AllJobs)?;
+        fossology.init_get_with_token("jobs/all")
+    } else {
+        fossology.init_get_with_token("jobs")
+    };
+
+    builder = if let Some(upload_id) = filter.upload_id {
+        builder.query("upload", upload_id)
+    } else {
+        builder
+    };
+
+    builder = if let Some(status) = filter.status.and_then(JobStatus::as_str) {
+        builder.query("status", status)
+    } else {
+        builder
+    };
+
+    let params = [
+        ("groupName", filter.group_name.clone()),
+        ("limit", limit.map(|limit| limit.to_string())),
+        ("page", page.map(|page| page.to_string())),
+    ];
+
+    for (name, value) in params {
+        if let Some(value) = value {
+            builder = builder.param(name, value);
+        }
+    }
+
+    let response = builder.send()?;
+
+    let total_pages = total_pages(&response);
+
+    match response.json::<FossologyResponse<Vec<Job>>>()? {
+        FossologyResponse::Response(items) => Ok(JobPage { items, total_pages }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Filters for [`list_jobs`]. Filters that are `None` are not used.
+#[derive(Debug, Default, Clone)]
+pub struct JobFilter {
+    /// List the jobs of all users instead of only the current user. Requires an administrator.
+    pub all_users: bool,
+
+    pub upload_id: Option<i32>,
+
+    /// [`JobStatus::Unknown`] is not sent to the API, the jobs with statuses unknown to the crate
+    /// are only filtered from the response.
+    pub status: Option<JobStatus>,
+
+    /// Id of the user who created the job.
+    pub user_id: Option<i32>,
+
+    /// Only jobs queued at or after the date.
+    pub queued_after: Option<DateTime<Utc>>,
+
+    /// Only jobs queued before the date.
+    pub queued_before: Option<DateTime<Utc>>,
+
+    pub group_name: Option<String>,
+}
+
+impl JobFilter {
+    /// Returns true if the job matches all of the filters.
+    pub fn matches(&self, job: &Job) -> bool {
+        self.upload_id.is_none_or(|id| job.upload_id == id)
+            && self.status.is_none_or(|status| job.status == status)
+            && self.user_id.is_none_or(|id| job.user_id == id)
+            && self.queued_after.is_none_or(|date| job.queue_date >= date)
+            && self.queued_before.is_none_or(|date| job.queue_date < date)
+    }
+}
+
+/// Page of jobs.
+pub type JobPage = Page<Job>;
+
+/// Iterator over all jobs matching a filter. Created with [`iter_jobs`].
+pub type JobIter<'a> = Paginator<'a, Job>;
+
 /// # Errors
 ///
 /// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
 /// - Response can't be serialized to [`ScheduledJob`] or [`Info`](crate::Info).
 /// - Response is not [`ScheduledJob`].
+/// - Any of the scheduled agents is not supported by the API version.
+/// - The agents are not valid, see [`ScheduleAgents::validate`].
 pub fn schedule_analysis(
     fossology: &Fossology,
     folder_id: i32,
@@ -66,17 +377,22 @@ pub fn schedule_analysis(
     group_name: Option<String>,
     analysis: &ScheduleAgents,
 ) -> Result<ScheduledJob, FossologyError> {
+    analysis.validate()?;
+    for capability in analysis.analysis.required_capabilities() {
+        fossology.require(capability)?;
+    }
+
     let mut builder = fossology.init_post_with_token("jobs").json(analysis);
 
     builder = if let Some(group_name) = group_name {
-        builder.header("groupName", group_name)
+        builder.param("groupName", group_name)
     } else {
         builder
     };
 
     let response = builder
-        .header("folderId", folder_id.to_string())
-        .header("uploadId", upload_id.to_string())
+        .param("folderId", folder_id.to_string())
+        .param("uploadId", upload_id.to_string())
         .json(analysis)
         .send()?
         .json::<FossologyResponse<InfoWithNumber>>()?;
@@ -87,6 +403,41 @@ pub fn schedule_analysis(
     }
 }
 
+/// # Errors
+///
+/// - Bulk scan is not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`ScheduledJob`] or [`Info`](crate::Info).
+/// - Response is not [`ScheduledJob`].
+pub fn schedule_bulk_scan(
+    fossology: &Fossology,
+    upload_id: i32,
+    item_id: i32,
+    group_name: Option<String>,
+    bulk_scan: &BulkScan,
+) -> Result<ScheduledJob, FossologyError> {
+    fossology.require(Capability::BulkScan)?;
+
+    let mut builder = fossology
+        .init_post_with_token(&format!("uploads/{}/item/{}/bulk-scan", upload_id, item_id))
+        .json(bulk_scan);
+
+    builder = if let Some(group_name) = group_name {
+        builder.param("groupName", group_name)
+    } else {
+        builder
+    };
+
+    let response = builder
+        .send()?
+        .json::<FossologyResponse<InfoWithNumber>>()?;
+
+    match response {
+        FossologyResponse::Response(res) => Ok(ScheduledJob { id: res.message }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScheduledJob {
     pub id: i32,
@@ -99,6 +450,259 @@ pub struct ScheduleAgents {
     pub reuse: Reuse,
 }
 
+impl ScheduleAgents {
+    /// Start building the agents to schedule without any agents enabled.
+    pub fn builder() -> ScheduleAgentsBuilder {
+        ScheduleAgentsBuilder::default()
+    }
+
+    /// Agents of [`ScheduleAgentsBuilder::quick_license_scan`]. The preset doesn't use reuse or
+    /// the bulk reuse decider, so it doesn't need to be validated.
+    pub fn quick_license_scan() -> Self {
+        ScheduleAgentsBuilder::quick_license_scan().agents
+    }
+
+    /// Check that the deciders and reuse options can be used together.
+    ///
+    /// # Errors
+    ///
+    /// - Bulk reuse decider is combined with other deciders, as the other deciders don't work
+    ///   with it: <https://github.com/fossology/fossology/issues/1639>.
+    /// - Bulk reuse decider or reuse options are enabled without an upload to reuse.
+    pub fn validate(&self) -> Result<(), FossologyError> {
+        let decider = &self.decider;
+        if decider.bulk_reused && (decider.nomos_monk || decider.new_scanner || decider.ojo_decider)
+        {
+            return Err(FossologyError::InvalidAgents(
+                "bulk reuse decider can't be combined with other deciders".to_string(),
+            ));
+        }
+
+        let reuse = &self.reuse;
+        let reuses = decider.bulk_reused
+            || reuse.reuse_main
+            || reuse.reuse_enhanced
+            || reuse.reuse_report
+            || reuse.reuse_copyright;
+        if reuses && reuse.reuse_upload == 0 {
+            return Err(FossologyError::InvalidAgents(
+                "reuse options need an upload to reuse".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for [`ScheduleAgents`]. Start from [`ScheduleAgents::builder`] or one of the presets.
+#[derive(Debug, Default)]
+pub struct ScheduleAgentsBuilder {
+    agents: ScheduleAgents,
+}
+
+impl ScheduleAgentsBuilder {
+    /// License scanners nomos, monk and ojo with the deciders that conclude their matching
+    /// findings.
+    pub fn quick_license_scan() -> Self {
+        ScheduleAgents::builder()
+            .nomos()
+            .monk()
+            .ojo()
+            .nomos_monk_decider()
+            .ojo_decider()
+    }
+
+    /// All license, copyright, keyword and package agents with the deciders for the license
+    /// scanners.
+    pub fn full_compliance_scan() -> Self {
+        ScheduleAgents::builder()
+            .bucket()
+            .copyright_email_author()
+            .ecc()
+            .keyword()
+            .mime()
+            .monk()
+            .nomos()
+            .ojo()
+            .package()
+            .nomos_monk_decider()
+            .new_scanner_decider()
+            .ojo_decider()
+    }
+
+    /// Only the copyright, email and author scanner.
+    pub fn copyright_only() -> Self {
+        ScheduleAgents::builder().copyright_email_author()
+    }
+
+    #[must_use]
+    pub const fn bucket(mut self) -> Self {
+        self.agents.analysis.bucket = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn copyright_email_author(mut self) -> Self {
+        self.agents.analysis.copyright_email_author = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn ecc(mut self) -> Self {
+        self.agents.analysis.ecc = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn keyword(mut self) -> Self {
+        self.agents.analysis.keyword = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn mime(mut self) -> Self {
+        self.agents.analysis.mime = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn monk(mut self) -> Self {
+        self.agents.analysis.monk = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn nomos(mut self) -> Self {
+        self.agents.analysis.nomos = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn ojo(mut self) -> Self {
+        self.agents.analysis.ojo = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn package(mut self) -> Self {
+        self.agents.analysis.package = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn heritage(mut self) -> Self {
+        self.agents.analysis.heritage = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn compatibility(mut self) -> Self {
+        self.agents.analysis.compatibility = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn ipra(mut self) -> Self {
+        self.agents.analysis.ipra = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn scancode(mut self, scancode: Scancode) -> Self {
+        self.agents.analysis.scancode = Some(scancode);
+        self
+    }
+
+    /// Add an analysis option that is not modelled by the crate.
+    #[must_use]
+    pub fn analysis_option<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
+        self.agents
+            .analysis
+            .extra
+            .insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Conclude the licenses found by both nomos and monk.
+    #[must_use]
+    pub const fn nomos_monk_decider(mut self) -> Self {
+        self.agents.decider.nomos_monk = true;
+        self
+    }
+
+    /// Conclude the licenses from the bulk scans of the reused upload. Can't be combined with
+    /// the other deciders.
+    #[must_use]
+    pub const fn bulk_reused_decider(mut self) -> Self {
+        self.agents.decider.bulk_reused = true;
+        self
+    }
+
+    /// Conclude the licenses of files whose only findings are new since the reused upload.
+    #[must_use]
+    pub const fn new_scanner_decider(mut self) -> Self {
+        self.agents.decider.new_scanner = true;
+        self
+    }
+
+    /// Conclude the licenses found by ojo.
+    #[must_use]
+    pub const fn ojo_decider(mut self) -> Self {
+        self.agents.decider.ojo_decider = true;
+        self
+    }
+
+    /// Reuse the clearing decisions of the upload in the group.
+    #[must_use]
+    pub fn reuse(mut self, upload_id: i32, group: &str) -> Self {
+        self.agents.reuse.reuse_upload = upload_id;
+        self.agents.reuse.reuse_group = group.to_string();
+        self
+    }
+
+    /// Reuse the upload found with [`reuse::find_reuse`]. Replaces the previous reuse options.
+    #[must_use]
+    pub fn with_reuse(mut self, reuse: Reuse) -> Self {
+        self.agents.reuse = reuse;
+        self
+    }
+
+    #[must_use]
+    pub const fn reuse_main(mut self) -> Self {
+        self.agents.reuse.reuse_main = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn reuse_enhanced(mut self) -> Self {
+        self.agents.reuse.reuse_enhanced = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn reuse_report(mut self) -> Self {
+        self.agents.reuse.reuse_report = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn reuse_copyright(mut self) -> Self {
+        self.agents.reuse.reuse_copyright = true;
+        self
+    }
+
+    /// # Errors
+    ///
+    /// - The agents are not valid, see [`ScheduleAgents::validate`].
+    pub fn build(self) -> Result<ScheduleAgents, FossologyError> {
+        self.agents.validate()?;
+
+        Ok(self.agents)
+    }
+}
+
+/// Agents to run. The agents that are not supported by all versions of the API are only sent
+/// when they are enabled.
 #[derive(Debug, Serialize, Default)]
 pub struct Analysis {
     pub bucket: bool,
@@ -110,6 +714,57 @@ pub struct Analysis {
     pub nomos: bool,
     pub ojo: bool,
     pub package: bool,
+
+    /// Look up the files from Software Heritage.
+    #[serde(skip_serializing_if = "is_false")]
+    pub heritage: bool,
+
+    /// Check the compatibility of the licenses found in the files.
+    #[serde(skip_serializing_if = "is_false")]
+    pub compatibility: bool,
+
+    /// Find intellectual property rights related statements.
+    #[serde(skip_serializing_if = "is_false")]
+    pub ipra: bool,
+
+    /// Run scancode-toolkit with the options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scancode: Option<Scancode>,
+
+    /// Options that are not modelled by the crate, sent as they are. Keys are converted to
+    /// camelCase in API v2.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Analysis {
+    /// Capabilities needed by the enabled agents.
+    fn required_capabilities(&self) -> Vec<Capability> {
+        [
+            (self.heritage, Capability::SoftwareHeritage),
+            (self.compatibility, Capability::CompatibilityAgent),
+            (self.ipra, Capability::IpraAgent),
+            (self.scancode.is_some(), Capability::ScancodeAgent),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, capability)| *capability)
+        .collect()
+    }
+}
+
+/// What scancode-toolkit scans the files for.
+#[derive(Debug, Serialize, Default, Clone, Copy)]
+pub struct Scancode {
+    pub license: bool,
+    pub copyright: bool,
+    pub email: bool,
+    pub url: bool,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -120,6 +775,11 @@ pub struct Decider {
     bulk_reused: bool,
     pub new_scanner: bool,
     pub ojo_decider: bool,
+
+    /// Options that are not modelled by the crate, sent as they are. Keys are converted to
+    /// camelCase in API v2.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -130,73 +790,280 @@ pub struct Reuse {
     pub reuse_enhanced: bool,
     pub reuse_report: bool,
     pub reuse_copyright: bool,
+
+    /// Options that are not modelled by the crate, sent as they are. Keys are converted to
+    /// camelCase in API v2.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Reuse {
+    /// Reuse the upload in the group without any of the reuse options enabled.
+    pub fn new(upload_id: i32, group: &str) -> Self {
+        Self {
+            reuse_upload: upload_id,
+            reuse_group: group.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Rules for a bulk scan (monkbulk) of an upload.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkScan {
+    /// Licenses to add or remove for the files matching the reference text.
+    pub bulk_actions: Vec<BulkAction>,
+
+    /// Text phrase to search for.
+    pub ref_text: String,
+
+    pub bulk_scope: BulkScope,
+
+    /// Add the decisions even if the files already have a clearing decision.
+    pub force_decision: bool,
+
+    /// Ignore files that have been marked irrelevant.
+    pub ignore_irre: bool,
+
+    /// Characters used to split the text into tokens. `DEFAULT` uses Fossology's defaults.
+    pub delimiters: String,
+
+    /// Scan only the files that already have scanner findings.
+    pub scan_only_findings: bool,
 }
 
-#[derive(Debug, Deserialize)]
+impl BulkScan {
+    /// Bulk scan for the upload with Fossology's default delimiters.
+    pub fn new(ref_text: &str, bulk_actions: Vec<BulkAction>) -> Self {
+        Self {
+            bulk_actions,
+            ref_text: ref_text.to_string(),
+            bulk_scope: BulkScope::Upload,
+            force_decision: false,
+            ignore_irre: false,
+            delimiters: "DEFAULT".to_string(),
+            scan_only_findings: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkAction {
+    pub license_short_name: String,
+    pub license_text: String,
+    pub acknowledgement: String,
+    pub comment: String,
+    pub license_action: LicenseAction,
+}
+
+impl BulkAction {
+    /// Add license with the given short name.
+    pub fn add(license_short_name: &str) -> Self {
+        Self::new(license_short_name, LicenseAction::Add)
+    }
+
+    /// Remove license with the given short name.
+    pub fn remove(license_short_name: &str) -> Self {
+        Self::new(license_short_name, LicenseAction::Remove)
+    }
+
+    fn new(license_short_name: &str, license_action: LicenseAction) -> Self {
+        Self {
+            license_short_name: license_short_name.to_string(),
+            license_text: String::new(),
+            acknowledgement: String::new(),
+            comment: String::new(),
+            license_action,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LicenseAction {
+    Add,
+    Remove,
+}
+
+/// Whether the bulk rules apply to the upload or to the whole folder.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkScope {
+    Folder,
+    Upload,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Job {
     pub id: i32,
 
     pub name: String,
 
-    pub queue_date: String,
+    #[serde(deserialize_with = "deserialize_date_time")]
+    pub queue_date: DateTime<Utc>,
 
-    pub upload_id: String,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub upload_id: i32,
 
-    pub user_id: String,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub user_id: i32,
 
-    pub group_id: String,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub group_id: i32,
 
     pub eta: i32,
 
     pub status: JobStatus,
+
+    /// Agents run by the job. Not reported by older versions of the API.
+    #[serde(default)]
+    pub job_queue: Vec<JobQueue>,
+}
+
+impl Job {
+    /// Queue entries of the agents that failed.
+    pub fn failed_agents(&self) -> impl Iterator<Item = &JobQueue> {
+        self.job_queue.iter().filter(|queue| queue.has_failed())
+    }
+}
+
+/// Run of a single agent in a job.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobQueue {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub job_queue_id: i32,
+
+    /// Name of the agent, like `nomos` or `ojo`.
+    pub job_queue_type: String,
+
+    #[serde(default, deserialize_with = "deserialize_optional_date_time")]
+    pub start_time: Option<DateTime<Utc>>,
+
+    #[serde(default, deserialize_with = "deserialize_optional_date_time")]
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Status text of the agent, like `Completed`, or the error that stopped it.
+    #[serde(default)]
+    pub status: String,
+
+    #[serde(default)]
+    pub items_processed: i64,
+
+    #[serde(default)]
+    pub items_per_sec: Option<f64>,
+
+    /// Path of the agent's log file on the server.
+    ///
+    /// The API doesn't serve the content of the log, so it can only be read on the server, for
+    /// example in the job details of the web interface.
+    #[serde(default)]
+    pub log: Option<String>,
+
+    /// Ids of the queue entries that have to finish before this one starts.
+    #[serde(default)]
+    pub dependencies: Vec<i32>,
+
+    #[serde(default)]
+    pub is_in_progress: bool,
+
+    #[serde(default)]
+    pub is_ready: bool,
+}
+
+impl JobQueue {
+    /// Returns true if the agent stopped without completing.
+    pub fn has_failed(&self) -> bool {
+        !self.is_in_progress
+            && self.end_time.is_some()
+            && !self.status.is_empty()
+            && self.status != "Completed"
+    }
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum JobStatus {
     Completed,
     Failed,
     Queued,
     Processing,
+    Killed,
+    Paused,
+
+    /// Status not known by this version of the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+impl JobStatus {
+    /// Returns true if the job has stopped running and will not continue. Paused jobs and jobs
+    /// with unknown statuses can still continue.
+    pub const fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Killed)
+    }
+
+    /// Name of the status in the API. `None` for [`JobStatus::Unknown`], which the API doesn't
+    /// have.
+    const fn as_str(self) -> Option<&'static str> {
+        match self {
+            Self::Completed => Some("Completed"),
+            Self::Failed => Some("Failed"),
+            Self::Queued => Some("Queued"),
+            Self::Processing => Some("Processing"),
+            Self::Killed => Some("Killed"),
+            Self::Paused => Some("Paused"),
+            Self::Unknown => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use std::{thread, time::Duration};
+    use chrono::Utc;
+    use serde_json::json;
 
-    use crate::{auth::test::create_test_fossology_with_writetoken, upload::new_upload_from_file};
+    use crate::{
+        auth::{tokens, TokenScope, TokensParameters},
+        testing::MockFossology,
+        upload::{get_top_item, new_upload_from_file},
+    };
 
     use super::*;
 
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
+
     #[test]
     fn get_unarchive_job() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        mock.set_processing_polls(1);
+        let fossology = mock.fossology();
 
-        let upload =
-            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
 
         let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
 
         assert_eq!(jobs.len(), 1);
         assert_eq!(jobs[0].status, JobStatus::Processing);
+
+        let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+
+        assert_eq!(jobs[0].status, JobStatus::Completed);
     }
 
     #[test]
     fn schedule_jobs() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
-        let upload =
-            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
 
         let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
 
         assert_eq!(jobs.len(), 1);
-
-        while get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap()[0].status
-            == JobStatus::Processing
-        {
-            thread::sleep(Duration::from_secs(1));
-        }
+        assert_eq!(jobs[0].status, JobStatus::Completed);
 
         let mut schedule = ScheduleAgents::default();
         schedule.analysis.nomos = true;
@@ -213,4 +1080,394 @@ mod test {
         assert_eq!(jobs.len(), 2);
         assert!(jobs.iter().any(|j| j.id == scheduled_job.id));
     }
+
+    #[test]
+    fn bulk_scan_is_unsupported_before_1_5_1() {
+        let mock = MockFossology::with_version("1.5.0");
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let item_id = get_top_item(&fossology, upload.upload_id).unwrap();
+        let bulk_scan = BulkScan::new("Copyright", vec![BulkAction::add("MIT")]);
+
+        assert!(matches!(
+            schedule_bulk_scan(&fossology, upload.upload_id, item_id, None, &bulk_scan),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::BulkScan,
+                ..
+            })
+        ));
+        let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+        assert_eq!(jobs.len(), 1);
+    }
+
+    #[test]
+    fn schedule_bulk_scan_for_upload() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let item_id = get_top_item(&fossology, upload.upload_id).unwrap();
+        let bulk_scan = BulkScan::new(
+            "Permission is hereby granted, free of charge",
+            vec![BulkAction::add("MIT")],
+        );
+
+        let scheduled_job =
+            schedule_bulk_scan(&fossology, upload.upload_id, item_id, None, &bulk_scan).unwrap();
+
+        let job = get_job_by_id(&fossology, scheduled_job.id)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(job.upload_id, upload.upload_id);
+        assert_eq!(job.job_queue[0].job_queue_type, "monkbulk");
+    }
+
+    #[test]
+    fn failed_agents_are_reported_in_job_queue() {
+        let mock = MockFossology::start();
+        mock.set_processing_polls(1);
+        mock.fail_agent("nomos");
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.nomos = true;
+        agents.analysis.ojo = true;
+        let job = schedule_analysis(&fossology, 1, upload.upload_id, None, &agents).unwrap();
+
+        let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+        let scheduled = jobs.iter().find(|j| j.id == job.id).unwrap();
+        assert_eq!(scheduled.job_queue.len(), 2);
+        assert!(scheduled.job_queue.iter().all(|queue| queue.is_in_progress));
+        assert_eq!(scheduled.failed_agents().count(), 0);
+
+        let jobs = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+        let unpack = jobs.iter().find(|j| j.id != job.id).unwrap();
+        assert_eq!(unpack.status, JobStatus::Completed);
+        assert_eq!(unpack.job_queue[0].job_queue_type, "ununpack");
+        assert_eq!(unpack.job_queue[0].items_processed, 42);
+
+        let scheduled = jobs.iter().find(|j| j.id == job.id).unwrap();
+        assert_eq!(scheduled.status, JobStatus::Failed);
+        let failed = scheduled.failed_agents().collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].job_queue_type, "nomos");
+        assert_eq!(failed[0].status, "Failed");
+        assert!(failed[0].end_time.is_some());
+        assert!(failed[0].log.is_some());
+    }
+
+    #[test]
+    fn jobs_are_listed_with_filters() {
+        let mock = MockFossology::with_version("1.5.0");
+        mock.fail_agent("nomos");
+        let fossology = mock.fossology();
+
+        let first = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let second = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.nomos = true;
+        let failing = schedule_analysis(&fossology, 1, second.upload_id, None, &agents).unwrap();
+
+        let all = JobFilter {
+            all_users: true,
+            ..JobFilter::default()
+        };
+        let jobs = iter_jobs(&fossology, &all)
+            .page_size(2)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        let page = list_jobs(&fossology, &all, Some(2), Some(2)).unwrap();
+        assert_eq!(page.total_pages, Some(2));
+        assert_eq!(page.items.len(), 1);
+
+        let failed = JobFilter {
+            status: Some(JobStatus::Failed),
+            ..JobFilter::default()
+        };
+        let jobs = iter_jobs(&fossology, &failed)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, failing.id);
+
+        let unknown = JobFilter {
+            status: Some(JobStatus::Unknown),
+            ..JobFilter::default()
+        };
+        assert_eq!(
+            list_jobs(&fossology, &unknown, None, None)
+                .unwrap()
+                .items
+                .len(),
+            0
+        );
+
+        let of_upload = JobFilter {
+            upload_id: Some(first.upload_id),
+            user_id: Some(3),
+            ..JobFilter::default()
+        };
+        assert_eq!(iter_jobs(&fossology, &of_upload).count(), 1);
+
+        let old = JobFilter {
+            queued_before: Some(Utc::now() - chrono::Duration::days(1)),
+            ..JobFilter::default()
+        };
+        assert_eq!(iter_jobs(&fossology, &old).count(), 0);
+
+        let job = get_job_by_id(&fossology, failing.id).unwrap().unwrap();
+        assert_eq!(job.upload_id, second.upload_id);
+        assert!(get_job_by_id(&fossology, 1000).unwrap().is_none());
+    }
+
+    #[test]
+    fn all_jobs_are_unsupported_before_1_5_0() {
+        let mock = MockFossology::with_version("1.4.0");
+        let fossology = mock.fossology();
+
+        let all = JobFilter {
+            all_users: true,
+            ..JobFilter::default()
+        };
+        assert!(matches!(
+            list_jobs(&fossology, &all, None, None),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::AllJobs,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_job_status_is_tolerated() {
+        let status: JobStatus = serde_json::from_str("\"Restarting\"").unwrap();
+        assert_eq!(status, JobStatus::Unknown);
+        let status: JobStatus = serde_json::from_str("\"Killed\"").unwrap();
+        assert_eq!(status, JobStatus::Killed);
+    }
+
+    #[test]
+    fn jobs_are_paused_resumed_and_killed() {
+        let mock = MockFossology::with_version("1.6.0");
+        mock.set_processing_polls(10);
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let job_id = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap()[0].id;
+        let status =
+            |fossology: &Fossology| get_job_by_id(fossology, job_id).unwrap().unwrap().status;
+
+        pause_job(&fossology, job_id).unwrap();
+        assert_eq!(status(&fossology), JobStatus::Paused);
+        resume_job(&fossology, job_id).unwrap();
+        assert_eq!(status(&fossology), JobStatus::Processing);
+        set_job_priority(&fossology, job_id, 10).unwrap();
+
+        assert!(kill_job(&fossology, job_id, Some(1)).is_err());
+        kill_job(&fossology, job_id, Some(job_id * 100)).unwrap();
+        assert_eq!(status(&fossology), JobStatus::Killed);
+        assert!(resume_job(&fossology, job_id).is_err());
+    }
+
+    #[test]
+    fn only_new_jobs_are_waited_for() {
+        let mock = MockFossology::with_version("1.6.0");
+        mock.fail_agent("nomos");
+        let fossology = mock.fossology();
+        let interval = Duration::from_millis(10);
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.nomos = true;
+        let failed = schedule_analysis(&fossology, 1, upload.upload_id, None, &agents).unwrap();
+        assert!(matches!(
+            wait_for_jobs(&fossology, upload.upload_id, &[], interval, None),
+            Err(FossologyError::JobFailed { .. })
+        ));
+
+        mock.set_processing_polls(100);
+        let paused = schedule_analysis(
+            &fossology,
+            1,
+            upload.upload_id,
+            None,
+            &ScheduleAgents::default(),
+        )
+        .unwrap();
+        pause_job(&fossology, paused.id).unwrap();
+        assert!(matches!(
+            wait_for_jobs(
+                &fossology,
+                upload.upload_id,
+                &[failed.id],
+                interval,
+                Some(Duration::from_millis(50))
+            ),
+            Err(FossologyError::Timeout(_))
+        ));
+
+        let jobs = wait_for_jobs(
+            &fossology,
+            upload.upload_id,
+            &[failed.id, paused.id],
+            interval,
+            None,
+        )
+        .unwrap();
+        assert_eq!(jobs.len(), 3);
+    }
+
+    #[test]
+    fn job_control_requires_permission() {
+        let mock = MockFossology::with_version("1.6.0");
+        let fossology = mock.fossology();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let job_id = get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap()[0].id;
+
+        let token = tokens(
+            &fossology,
+            &TokensParameters::new(
+                "fossy",
+                "fossy",
+                "read",
+                TokenScope::Read,
+                Utc::now().date_naive(),
+            ),
+        )
+        .unwrap();
+        let read_only = Fossology::new(
+            &mock.uri(),
+            token.authorization.trim_start_matches("Bearer "),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            kill_job(&read_only, job_id, None),
+            Err(FossologyError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            pause_job(&read_only, job_id),
+            Err(FossologyError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn job_control_is_unsupported_before_1_6_0() {
+        let mock = MockFossology::with_version("1.4.0");
+        let fossology = mock.fossology();
+
+        assert!(matches!(
+            kill_job(&fossology, 1, None),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::JobControl,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn extended_agents_are_scheduled() {
+        let mock = MockFossology::with_version("1.6.0");
+        let fossology = mock.fossology();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.ipra = true;
+        agents.analysis.compatibility = true;
+        agents.analysis.scancode = Some(Scancode {
+            license: true,
+            ..Scancode::default()
+        });
+        agents
+            .analysis
+            .extra
+            .insert("reso".to_string(), json!(true));
+        let job = schedule_analysis(&fossology, 1, upload.upload_id, None, &agents).unwrap();
+
+        let job = get_job_by_id(&fossology, job.id).unwrap().unwrap();
+        let mut scheduled = job
+            .job_queue
+            .iter()
+            .map(|queue| queue.job_queue_type.as_str())
+            .collect::<Vec<_>>();
+        scheduled.sort_unstable();
+        assert_eq!(scheduled, ["compatibility", "ipra", "reso", "scancode"]);
+    }
+
+    #[test]
+    fn agents_are_unsupported_by_older_versions() {
+        let mock = MockFossology::with_version("1.4.0");
+        let fossology = mock.fossology();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.nomos = true;
+        agents.analysis.ipra = true;
+        assert!(matches!(
+            schedule_analysis(&fossology, 1, upload.upload_id, None, &agents),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::IpraAgent,
+                ..
+            })
+        ));
+
+        agents.analysis.ipra = false;
+        agents.analysis.heritage = true;
+        schedule_analysis(&fossology, 1, upload.upload_id, None, &agents).unwrap();
+    }
+
+    #[test]
+    fn presets_enable_agents() {
+        let quick = ScheduleAgentsBuilder::quick_license_scan().build().unwrap();
+        assert!(quick.analysis.nomos && quick.analysis.monk && quick.analysis.ojo);
+        assert!(!quick.analysis.copyright_email_author);
+        assert!(quick.decider.nomos_monk && quick.decider.ojo_decider);
+
+        let full = ScheduleAgentsBuilder::full_compliance_scan()
+            .build()
+            .unwrap();
+        assert!(full.analysis.package && full.analysis.copyright_email_author);
+        assert!(full.decider.new_scanner);
+        assert!(!full.decider.bulk_reused);
+
+        let copyright = ScheduleAgentsBuilder::copyright_only().build().unwrap();
+        assert!(copyright.analysis.copyright_email_author);
+        assert!(!copyright.analysis.nomos);
+    }
+
+    #[test]
+    fn invalid_decider_combinations_are_rejected() {
+        assert!(matches!(
+            ScheduleAgentsBuilder::quick_license_scan()
+                .reuse(1, "fossy")
+                .bulk_reused_decider()
+                .build(),
+            Err(FossologyError::InvalidAgents(_))
+        ));
+        assert!(matches!(
+            ScheduleAgents::builder().bulk_reused_decider().build(),
+            Err(FossologyError::InvalidAgents(_))
+        ));
+        assert!(matches!(
+            ScheduleAgents::builder().nomos().reuse_main().build(),
+            Err(FossologyError::InvalidAgents(_))
+        ));
+
+        let reuse = ScheduleAgents::builder()
+            .nomos()
+            .reuse(1, "fossy")
+            .reuse_main()
+            .bulk_reused_decider()
+            .analysis_option("reso", true)
+            .build()
+            .unwrap();
+        assert_eq!(reuse.reuse.reuse_upload, 1);
+        assert_eq!(reuse.analysis.extra["reso"], Value::Bool(true));
+    }
 }
@@ -0,0 +1,185 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Find the upload whose clearing decisions are reused.
+//!
+//! Instead of the id of the upload to reuse, [`find_reuse`] takes the name of the package or the
+//! hash of the archive and finds the latest matching upload.
+
+use crate::{
+    upload::{find_uploads_by_hash, iter_uploads, latest, Hash, Upload, UploadFilter},
+    Fossology, FossologyError,
+};
+
+use super::Reuse;
+
+/// How the upload to reuse is found.
+#[derive(Debug, Clone, Copy)]
+pub enum ReuseSource<'a> {
+    /// Latest upload of the same package in the folder or its subfolders. The package is
+    /// compared with [`package_name`], so for example `base-files_11.tar.xz` matches
+    /// `base-files_12.1.tar.gz`.
+    Package { name: &'a str, folder_id: i32 },
+
+    /// Latest upload of the same archive, found with [`filesearch`](crate::upload::filesearch).
+    /// If the archive has not been uploaded on its own, the latest upload containing it is used.
+    Hash(&'a Hash),
+}
+
+/// Find the upload to reuse in the group and fill in [`Reuse`] for it. Returns `None` if there
+/// is no matching upload.
+///
+/// The reuse options are not enabled, set them on the returned [`Reuse`] or use
+/// [`ScheduleAgentsBuilder::with_reuse`](super::ScheduleAgentsBuilder::with_reuse).
+///
+/// # Errors
+///
+/// - Uploads can't be listed or searched, see [`find_reuse_upload`].
+pub fn find_reuse(
+    fossology: &Fossology,
+    source: ReuseSource<'_>,
+    group: &str,
+    exclude_upload_id: Option<i32>,
+) -> Result<Option<Reuse>, FossologyError> {
+    let upload = find_reuse_upload(fossology, source, group, exclude_upload_id)?;
+
+    Ok(upload.map(|upload| Reuse::new(upload.id, group)))
+}
+
+/// Find the best upload to reuse in the group. The upload with the id `exclude_upload_id`, like
+/// the upload being scanned, is never returned.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Uploads can't be listed, searched or retrieved.
+pub fn find_reuse_upload(
+    fossology: &Fossology,
+    source: ReuseSource<'_>,
+    group: &str,
+    exclude_upload_id: Option<i32>,
+) -> Result<Option<Upload>, FossologyError> {
+    let is_excluded = |id: i32| exclude_upload_id == Some(id);
+
+    match source {
+        ReuseSource::Package { name, folder_id } => {
+            let package = package_name(name);
+            let filter = UploadFilter {
+                folder_id: Some(folder_id),
+                name: Some(package.to_string()),
+                group_name: Some(group.to_string()),
+            };
+
+            let mut candidates = Vec::new();
+            for upload in iter_uploads(fossology, &filter) {
+                let upload = upload?;
+                if !is_excluded(upload.id) && package_name(&upload.upload_name) == package {
+                    candidates.push(upload);
+                }
+            }
+
+            Ok(latest(candidates))
+        }
+        ReuseSource::Hash(hash) => {
+            let (archives, containing) = find_uploads_by_hash(fossology, hash, Some(group))?;
+            let without_excluded = |uploads: Vec<Upload>| {
+                uploads
+                    .into_iter()
+                    .filter(|upload| !is_excluded(upload.id))
+                    .collect()
+            };
+
+            Ok(latest(without_excluded(archives)).or_else(|| latest(without_excluded(containing))))
+        }
+    }
+}
+
+/// Name of the package without the version and the archive extension, like `base-files` for
+/// `base-files_11.tar.xz`.
+///
+/// The version starts from the first `-` or `_` that is followed by a digit or by `v` and a
+/// digit. Names without a version end at the first `.`.
+pub fn package_name(upload_name: &str) -> &str {
+    let name = upload_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(upload_name);
+
+    let version_start = name.char_indices().find(|(i, c)| {
+        let rest = &name[i + c.len_utf8()..];
+        let rest = rest.strip_prefix('v').unwrap_or(rest);
+        matches!(c, '-' | '_') && rest.starts_with(|c: char| c.is_ascii_digit())
+    });
+
+    match version_start {
+        Some((i, _)) => &name[..i],
+        None => name.split('.').next().unwrap_or(name),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{testing::MockFossology, upload::new_upload_from_file};
+
+    use super::*;
+
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
+
+    #[test]
+    fn package_names_exclude_version_and_extension() {
+        assert_eq!(package_name("base-files_11.tar.xz"), "base-files");
+        assert_eq!(package_name("zlib-1.2.11.tar.gz"), "zlib");
+        assert_eq!(package_name("dir/openssl-v3.0.zip"), "openssl");
+        assert_eq!(package_name("my-lib.zip"), "my-lib");
+    }
+
+    #[test]
+    fn reuse_is_found_by_package_name() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let previous = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        new_upload_from_file(&fossology, 2, ARCHIVE).unwrap();
+        let current = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let source = ReuseSource::Package {
+            name: "base-files_12.tar.xz",
+            folder_id: 1,
+        };
+        let reuse = find_reuse(&fossology, source, "fossy", Some(current.upload_id))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reuse.reuse_upload, previous.upload_id);
+        assert_eq!(reuse.reuse_group, "fossy");
+
+        let source = ReuseSource::Package {
+            name: "other_1.0.tar.gz",
+            folder_id: 1,
+        };
+        assert!(find_reuse(&fossology, source, "fossy", None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn reuse_is_found_by_archive_hash() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+        let hash = Hash::from_file(ARCHIVE).unwrap();
+
+        new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let previous = new_upload_from_file(&fossology, 2, ARCHIVE).unwrap();
+        let current = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let upload = find_reuse_upload(
+            &fossology,
+            ReuseSource::Hash(&hash),
+            "fossy",
+            Some(current.upload_id),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(upload.id, previous.upload_id);
+    }
+}
@@ -15,19 +15,42 @@
 )]
 
 use log::error;
-use reqwest::blocking::{Client, RequestBuilder};
-use serde::Deserialize;
-use std::time::Duration;
-use version_compare::{CompOp, VersionCompare};
+use reqwest::{
+    blocking::{Body, Request, Response},
+    header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE},
+    Method, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{hash_map::RandomState, VecDeque},
+    convert::TryFrom,
+    hash::{BuildHasher, Hasher},
+    io::{Cursor, Read, Write},
+    path::Path,
+    time::Duration,
+};
 
-use crate::info::{ApiInformation, ApiInformationV1};
+use crate::{
+    cassette::{Recorder, Replayer},
+    info::{ApiInformation, ApiInformationV1},
+    transport::{HttpTransport, Transport},
+    version::{ApiVersion, Capability},
+};
 
 pub mod auth;
+pub mod cassette;
 pub mod info;
 pub mod job;
 pub mod license;
+pub mod report;
+pub mod scan;
+pub mod search;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
 pub mod upload;
 mod utilities;
+pub mod version;
 
 /// Client for the Fossology API.
 #[derive(Debug)]
@@ -38,13 +61,19 @@ pub struct Fossology {
     /// Access token for Fossology.
     token: String,
 
-    /// Reqwest client.
-    client: Client,
+    /// Sends the requests to the API.
+    transport: Box<dyn Transport>,
 
     /// Version of the Fossology API. Is retrieved during creation.
-    version: String,
+    version: ApiVersion,
 }
 
+/// Timeout of the requests, unless overridden for a request.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Host used for the requests when replaying a cassette.
+const REPLAY_HOST: &str = "http://replay.invalid";
+
 /// Error when interacting with Fossology.
 #[derive(Debug, thiserror::Error)]
 pub enum FossologyError {
@@ -60,8 +89,35 @@ pub enum FossologyError {
     #[error(transparent)]
     SerdeError(#[from] serde_json::Error),
 
-    #[error("Fossology version does not support the endpoint.")]
-    UnsupportedVersion,
+    #[error(
+        "Fossology API version {actual} does not support {capability}, {required} is required."
+    )]
+    UnsupportedVersion {
+        capability: Capability,
+        required: ApiVersion,
+        actual: ApiVersion,
+    },
+
+    /// Scheduled agents can't be used together.
+    #[error("Invalid agents: {0}")]
+    InvalidAgents(String),
+
+    /// The token doesn't have the permission for the operation.
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// Waiting for the server did not finish in time.
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// Jobs of the upload failed. Contains the failed agents and their statuses, if reported by
+    /// the API.
+    #[error("Jobs of upload {upload_id} failed: {}", .agents.join(", "))]
+    JobFailed { upload_id: i32, agents: Vec<String> },
+
+    /// The request can't be built, for example because a header value is invalid.
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
 
     #[error("Error: {0}")]
     Other(String),
@@ -86,6 +142,67 @@ impl<T> FossologyResponse<T> {
     }
 }
 
+/// Number of pages available for a paginated endpoint, if reported by the API.
+pub(crate) fn total_pages(response: &Response) -> Option<i32> {
+    response
+        .headers()
+        .get("X-Total-Pages")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+/// Parse the [`Info`] returned by an operation. Forbidden operations are returned as
+/// [`FossologyError::PermissionDenied`].
+pub(crate) fn info_response(response: Response) -> Result<Info, FossologyError> {
+    if !response.status().is_success() {
+        return Err(error_response(response));
+    }
+
+    let bytes = response.bytes()?;
+    serde_json::from_slice::<Info>(&bytes).map_err(|_| {
+        FossologyError::UnexpectedResponse(String::from_utf8_lossy(&bytes).to_string())
+    })
+}
+
+/// Parse the `T` returned by a successful request. Errors are returned like in
+/// [`info_response`], so forbidden requests are [`FossologyError::PermissionDenied`].
+pub(crate) fn json_response<T: DeserializeOwned>(response: Response) -> Result<T, FossologyError> {
+    if !response.status().is_success() {
+        return Err(error_response(response));
+    }
+
+    Ok(response.json::<T>()?)
+}
+
+/// Error of an unsuccessful response, with the message of the [`Info`] in its body.
+fn error_response(response: Response) -> FossologyError {
+    let status = response.status();
+    let bytes = match response.bytes() {
+        Ok(bytes) => bytes,
+        Err(err) => return err.into(),
+    };
+
+    match serde_json::from_slice::<Info>(&bytes) {
+        Ok(info) if status == StatusCode::FORBIDDEN || status == StatusCode::UNAUTHORIZED => {
+            FossologyError::PermissionDenied(info.message)
+        }
+        Ok(info) => FossologyError::Other(info.message),
+        Err(_) => FossologyError::UnexpectedResponse(String::from_utf8_lossy(&bytes).to_string()),
+    }
+}
+
+/// Stream the body of a successful response into `writer`.
+pub(crate) fn copy_response_to<W: Write + ?Sized>(
+    mut response: Response,
+    writer: &mut W,
+) -> Result<u64, FossologyError> {
+    if !response.status().is_success() {
+        return Err(error_response(response));
+    }
+
+    Ok(response.copy_to(writer)?)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Info {
     pub code: i32,
@@ -102,6 +219,99 @@ pub struct InfoWithNumber {
     pub error_type: String,
 }
 
+/// Page of items from a paginated endpoint.
+///
+/// Filters of listings are also applied to the received items, because not all versions of the
+/// API support them. A filtered page can therefore contain fewer items than requested. The
+/// [`Paginator`] still continues to the next page.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+
+    /// Number of pages available, if reported by the API.
+    pub total_pages: Option<i32>,
+}
+
+/// Retrieves the page with the given size and number.
+type FetchPage<'a, T> = Box<dyn FnMut(i32, i32) -> Result<Page<T>, FossologyError> + 'a>;
+
+/// Iterator over all items of a paginated endpoint, retrieving the items page by page.
+///
+/// The iteration stops at the first error.
+pub struct Paginator<'a, T> {
+    fetch: FetchPage<'a, T>,
+    retain: Box<dyn Fn(&T) -> bool + 'a>,
+    page_size: i32,
+    next_page: Option<i32>,
+    items: VecDeque<T>,
+}
+
+impl<'a, T> Paginator<'a, T> {
+    /// Number of items requested per page by default.
+    pub const DEFAULT_PAGE_SIZE: i32 = 100;
+
+    /// Paginate with `fetch`, which is called with the page size and the page number starting
+    /// from 1.
+    pub(crate) fn new(fetch: impl FnMut(i32, i32) -> Result<Page<T>, FossologyError> + 'a) -> Self {
+        Self {
+            fetch: Box::new(fetch),
+            retain: Box::new(|_| true),
+            page_size: Self::DEFAULT_PAGE_SIZE,
+            next_page: Some(1),
+            items: VecDeque::new(),
+        }
+    }
+
+    /// Only return the received items for which `retain` returns true. Whether more pages are
+    /// available is decided before the items are dropped.
+    pub(crate) fn retain(mut self, retain: impl Fn(&T) -> bool + 'a) -> Self {
+        self.retain = Box::new(retain);
+        self
+    }
+
+    /// Set the number of items requested per page.
+    #[must_use]
+    pub const fn page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+}
+
+impl<T> Iterator for Paginator<'_, T> {
+    type Item = Result<T, FossologyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.items.is_empty() {
+            let page_number = self.next_page?;
+
+            let page = match (self.fetch)(self.page_size, page_number) {
+                Ok(page) => page,
+                Err(err) => {
+                    self.next_page = None;
+                    return Some(Err(err));
+                }
+            };
+
+            let has_more_pages = match page.total_pages {
+                Some(total_pages) => page_number < total_pages,
+                None => usize::try_from(self.page_size).is_ok_and(|size| page.items.len() >= size),
+            };
+
+            self.next_page = if has_more_pages && !page.items.is_empty() {
+                Some(page_number + 1)
+            } else {
+                None
+            };
+
+            let retain = &self.retain;
+            self.items
+                .extend(page.items.into_iter().filter(|item| retain(item)));
+        }
+
+        self.items.pop_front().map(Ok)
+    }
+}
+
 impl Fossology {
     /// Creates a client for Fossology API.
     ///
@@ -112,36 +322,80 @@ impl Fossology {
     ///
     /// - API version can't be retrieved.
     pub fn new(uri: &str, token: &str) -> Result<Self, FossologyError> {
-        let version = Self::version(uri, token)?;
-        let client = Client::builder()
-            .timeout(Duration::from_secs(600))
-            .build()?;
-        let fossology = Self {
+        Self::with_transport(uri, token, HttpTransport::new()?)
+    }
+
+    /// Creates a client that records every request and response to the cassette file at
+    /// `cassette`, so the interactions can later be replayed with [`Fossology::replaying`].
+    ///
+    /// The file is overwritten and the interactions are written to it when the client is
+    /// dropped. The access token is not recorded.
+    /// Bodies that are not JSON, like downloads, are streamed to files next to the cassette.
+    ///
+    /// # Errors
+    ///
+    /// - `uri` is not a valid URL or the API version can't be retrieved.
+    /// - Cassette can't be written.
+    pub fn recording<P: AsRef<Path>>(
+        uri: &str,
+        token: &str,
+        cassette: P,
+    ) -> Result<Self, FossologyError> {
+        let recorder = Recorder::new(HttpTransport::new()?, uri, cassette)?;
+
+        Self::with_transport(uri, token, recorder)
+    }
+
+    /// Creates a client that responds to requests from the cassette file recorded with
+    /// [`Fossology::recording`], without connecting to a server.
+    ///
+    /// Every recorded interaction is used once, in the order they were recorded. Requests that
+    /// don't match any remaining interaction fail. The requests are sent to the base path of the
+    /// recorded API, like `/repo/api/v2`.
+    ///
+    /// # Errors
+    ///
+    /// - Cassette can't be read.
+    /// - Cassette doesn't contain the version of the API.
+    pub fn replaying<P: AsRef<Path>>(cassette: P) -> Result<Self, FossologyError> {
+        let replayer = Replayer::new(REPLAY_HOST, cassette)?;
+        let uri = replayer.base_uri().to_owned();
+
+        Self::with_transport(&uri, "", replayer)
+    }
+
+    /// Creates a client for Fossology API that sends the requests with `transport`.
+    ///
+    /// # Errors
+    ///
+    /// - API version can't be retrieved.
+    pub fn with_transport<T: Transport + 'static>(
+        uri: &str,
+        token: &str,
+        transport: T,
+    ) -> Result<Self, FossologyError> {
+        let mut fossology = Self {
             uri: uri.to_owned(),
             token: token.to_owned(),
-            client,
-            version,
+            transport: Box::new(transport),
+            version: ApiVersion::new(0, 0, 0),
         };
+        fossology.version = fossology.version()?.parse()?;
 
         Ok(fossology)
     }
 
     /// Get the version of the API. Tries different endpoints to get version for older and newer
     /// instances.
-    fn version(uri: &str, token: &str) -> Result<String, FossologyError> {
-        let client = Client::new();
-        let info = client
-            .get(&format!("{}/info", uri))
-            .bearer_auth(token)
+    fn version(&self) -> Result<String, FossologyError> {
+        let info = self
+            .init_get_with_token("info")
             .send()?
             .json::<ApiInformation>();
         if let Ok(info) = info {
             Ok(info.version)
         } else {
-            let version = client
-                .get(&format!("{}/version", uri))
-                .send()?
-                .json::<ApiInformationV1>();
+            let version = self.init_get("version").send()?.json::<ApiInformationV1>();
             match version {
                 Ok(version) => Ok(version.version),
                 Err(err) => Err(FossologyError::Other(err.to_string())),
@@ -149,44 +403,316 @@ impl Fossology {
         }
     }
 
-    /// Returns true if the API version is at least the given version.
-    pub(crate) fn version_is_at_least(&self, version: &str) -> Result<bool, FossologyError> {
-        VersionCompare::compare_to(&self.version, version, &CompOp::Ge)
-            .map_err(|_| FossologyError::Other("Failed to compare versions".to_string()))
+    /// Version of the API, retrieved when the client was created.
+    pub const fn api_version(&self) -> ApiVersion {
+        self.version
+    }
+
+    /// Returns true if the version of the API supports the capability.
+    pub fn supports(&self, capability: Capability) -> bool {
+        capability.is_supported_by(self.version)
+    }
+
+    /// Returns [`FossologyError::UnsupportedVersion`] if the version of the API doesn't support
+    /// the capability.
+    pub(crate) fn require(&self, capability: Capability) -> Result<(), FossologyError> {
+        if self.supports(capability) {
+            Ok(())
+        } else {
+            Err(FossologyError::UnsupportedVersion {
+                capability,
+                required: capability.required_version(),
+                actual: self.version,
+            })
+        }
     }
 
     /// Initializes `GET` request with the authorization token.
-    pub(crate) fn init_get_with_token(&self, path: &str) -> RequestBuilder {
-        self.client
-            .get(&format!("{}/{}", self.uri, path))
-            .bearer_auth(&self.token)
+    pub(crate) fn init_get_with_token(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::GET, path).bearer_auth()
     }
 
     /// Initializes `GET` request without the authorization token.
-    pub(crate) fn init_get(&self, path: &str) -> RequestBuilder {
-        self.client.get(&format!("{}/{}", self.uri, path))
+    pub(crate) fn init_get(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::GET, path)
     }
 
     /// Initializes `POST` request with the authorization token.
-    pub(crate) fn init_post_with_token(&self, path: &str) -> RequestBuilder {
-        self.client
-            .post(&format!("{}/{}", self.uri, path))
-            .bearer_auth(&self.token)
+    pub(crate) fn init_post_with_token(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::POST, path).bearer_auth()
     }
+
+    /// Initializes `POST` request without the authorization token.
+    pub(crate) fn init_post(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::POST, path)
+    }
+
+    /// Initializes `PATCH` request with the authorization token.
+    pub(crate) fn init_patch_with_token(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::PATCH, path).bearer_auth()
+    }
+
+    /// Initializes `PUT` request with the authorization token.
+    pub(crate) fn init_put_with_token(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::PUT, path).bearer_auth()
+    }
+
+    /// Initializes `DELETE` request with the authorization token.
+    pub(crate) fn init_delete_with_token(&self, path: &str) -> ApiRequest<'_> {
+        self.request(Method::DELETE, path).bearer_auth()
+    }
+
+    /// The request is built without a reqwest client, so that only the transport needs one. The
+    /// timeout is set on the request, so that it also applies to the clients of custom
+    /// transports.
+    fn request(&self, method: Method, path: &str) -> ApiRequest<'_> {
+        let url = format!("{}/{}", self.uri, path);
+        let request = Url::parse(&url)
+            .map(|url| {
+                let mut request = Request::new(method, url);
+                *request.timeout_mut() = Some(DEFAULT_TIMEOUT);
+                request
+            })
+            .map_err(|err| FossologyError::InvalidRequest(format!("{}: {}", url, err)));
+
+        ApiRequest {
+            fossology: self,
+            request,
+        }
+    }
+}
+
+/// Request to the API that is sent with the transport of the client. Errors while building the
+/// request are returned when it is sent.
+pub(crate) struct ApiRequest<'a> {
+    fossology: &'a Fossology,
+    request: Result<Request, FossologyError>,
+}
+
+impl ApiRequest<'_> {
+    /// Changes the request, unless building it has already failed.
+    fn with<F>(self, change: F) -> Self
+    where
+        F: FnOnce(&mut Request) -> Result<(), FossologyError>,
+    {
+        let request = self.request.and_then(|mut request| {
+            change(&mut request)?;
+            Ok(request)
+        });
+
+        Self { request, ..self }
+    }
+
+    fn bearer_auth(self) -> Self {
+        let token = format!("Bearer {}", self.fossology.token);
+        self.with(|request| {
+            let mut value = header_value(AUTHORIZATION.as_str(), &token)?;
+            value.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, value);
+            Ok(())
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub(crate) fn header<V: ToString>(self, name: &'static str, value: V) -> Self {
+        let value = value.to_string();
+        self.with(|request| {
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|err| FossologyError::InvalidRequest(format!("{}: {}", name, err)))?;
+            let value = header_value(name, &value)?;
+            request.headers_mut().append(header, value);
+            Ok(())
+        })
+    }
+
+    /// Adds a parameter that is sent as a header in API v1 and as a query parameter in API v2.
+    pub(crate) fn param<V: ToString>(self, name: &'static str, value: V) -> Self {
+        if self.fossology.supports(Capability::ApiV2) {
+            self.query(name, value)
+        } else {
+            self.header(name, value)
+        }
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub(crate) fn query<V: ToString>(self, name: &str, value: V) -> Self {
+        self.with(|request| {
+            request
+                .url_mut()
+                .query_pairs_mut()
+                .append_pair(name, &value.to_string());
+            Ok(())
+        })
+    }
+
+    /// Sets the body of the request. The keys of the payload are converted to camelCase in API
+    /// v2.
+    pub(crate) fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        let v2 = self.fossology.supports(Capability::ApiV2);
+        self.with(|request| {
+            let body = match serde_json::to_value(json) {
+                Ok(value) if v2 => serde_json::to_vec(&utilities::camel_case_keys(value))?,
+                _ => serde_json::to_vec(json)?,
+            };
+            request
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            *request.body_mut() = Some(Body::from(body));
+            Ok(())
+        })
+    }
+
+    /// Sets the body to a `multipart/form-data` form with the file `file_name` in the field
+    /// `name`. The `length` bytes of the file are streamed from `reader`.
+    pub(crate) fn file<R: Read + Send + 'static>(
+        self,
+        name: &str,
+        file_name: &str,
+        reader: R,
+        length: u64,
+    ) -> Self {
+        let boundary = multipart_boundary();
+        let head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            boundary,
+            name,
+            quoted_string(file_name)
+        );
+        let tail = format!("\r\n--{}--\r\n", boundary);
+        let content_type = format!("multipart/form-data; boundary={}", boundary);
+
+        self.with(|request| {
+            let size = (head.len() + tail.len()) as u64 + length;
+            let body = Cursor::new(head).chain(reader).chain(Cursor::new(tail));
+            request
+                .headers_mut()
+                .insert(CONTENT_TYPE, header_value("Content-Type", &content_type)?);
+            *request.body_mut() = Some(Body::sized(body, size));
+            Ok(())
+        })
+    }
+
+    /// Overrides the default timeout of the request.
+    pub(crate) fn timeout(self, timeout: Duration) -> Self {
+        self.with(|request| {
+            *request.timeout_mut() = Some(timeout);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn send(self) -> Result<Response, FossologyError> {
+        self.fossology.transport.execute(self.request?)
+    }
+}
+
+fn header_value(name: &str, value: &str) -> Result<HeaderValue, FossologyError> {
+    HeaderValue::from_str(value)
+        .map_err(|err| FossologyError::InvalidRequest(format!("{}: {}", name, err)))
+}
+
+/// Random boundary of a multipart body.
+fn multipart_boundary() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+
+    format!("{:016x}{:016x}", random(), random())
+}
+
+/// Escapes the quotes and line breaks of a quoted parameter in a header.
+fn quoted_string(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
 }
 
 #[cfg(test)]
 mod tests {
-    use version_compare::{CompOp, VersionCompare};
+    use std::convert::TryFrom;
+
+    use crate::{
+        job::{get_jobs, schedule_analysis, ScheduleAgents},
+        license::get_license,
+        testing::MockFossology,
+        upload::{filesearch, get_upload_by_id, new_upload_from_file, Hash},
+    };
+
+    use super::{ApiVersion, Capability, Fossology, Page, Paginator};
 
-    use super::Fossology;
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
 
     #[test]
     fn fossology_is_created() {
-        let fossology = Fossology::new("http://localhost:8080/repo/api/v1", "token").unwrap();
+        let mock = MockFossology::with_version("1.4.0");
+        let fossology = Fossology::new(&mock.uri(), "token").unwrap();
 
         assert_eq!(fossology.token, "token");
-        assert!(VersionCompare::compare_to(&fossology.version, "1.0.0", &CompOp::Ge).unwrap());
-        assert!(VersionCompare::compare_to(&fossology.version, "2.0.0", &CompOp::Lt).unwrap());
+        assert_eq!(fossology.api_version(), ApiVersion::new(1, 4, 0));
+    }
+
+    #[test]
+    fn pages_are_retrieved_until_the_last_page() {
+        let items = (1..=5).collect::<Vec<i32>>();
+        let paginate = |total_pages: Option<i32>| {
+            let items = &items;
+            Paginator::new(move |limit, page| {
+                let items = items
+                    .chunks(usize::try_from(limit).unwrap())
+                    .nth(usize::try_from(page - 1).unwrap())
+                    .unwrap_or_default()
+                    .to_vec();
+                Ok(Page { items, total_pages })
+            })
+            .page_size(2)
+        };
+
+        let all = paginate(None).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(all, items);
+
+        let reported = paginate(Some(2)).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(reported, [1, 2, 3, 4]);
+
+        let odd = paginate(None)
+            .retain(|item| item % 2 == 1)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(odd, [1, 3, 5]);
+    }
+
+    #[test]
+    fn api_v2_is_used_from_2_0_0() {
+        let mock = MockFossology::with_version("2.0.0");
+        assert!(mock.uri().ends_with("/repo/api/v2"));
+        let fossology = mock.fossology();
+        assert!(fossology.supports(Capability::ApiV2));
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let uploaded = get_upload_by_id(&fossology, upload.upload_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(uploaded.folder_id, 1);
+        assert_eq!(uploaded.upload_name, "base-files_11.tar.xz");
+
+        let job = schedule_analysis(
+            &fossology,
+            1,
+            upload.upload_id,
+            Some("fossy".to_string()),
+            &ScheduleAgents::default(),
+        )
+        .unwrap();
+        let jobs = get_jobs(&fossology, Some(upload.upload_id), None, Some(10), Some(1)).unwrap();
+        assert!(jobs.iter().any(|j| j.id == job.id));
+
+        let hash = Hash::from_file(ARCHIVE).unwrap();
+        let results = filesearch(&fossology, &[hash], Some("fossy".to_string())).unwrap();
+        assert_eq!(results[0].uploads, vec![upload.upload_id]);
+
+        assert_eq!(
+            get_license(&fossology, "MIT", Some("fossy"))
+                .unwrap()
+                .full_name,
+            "MIT License"
+        );
     }
 }
@@ -4,9 +4,9 @@
 
 //! Get licenses from the API.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{Fossology, FossologyError, FossologyResponse};
+use crate::{version::Capability, Fossology, FossologyError, FossologyResponse};
 
 /// # Errors
 ///
@@ -18,7 +18,7 @@ pub fn get_license(
     short_name: &str,
     group_name: Option<&str>,
 ) -> Result<License, FossologyError> {
-    let mut builder = if fossology.version_is_at_least("1.3.0")? {
+    let mut builder = if fossology.supports(Capability::LicensePathParam) {
         fossology.init_get_with_token(&format!("license/{}", short_name))
     } else {
         fossology
@@ -26,7 +26,7 @@ pub fn get_license(
             .header("shortName", short_name)
     };
     builder = if let Some(group_name) = group_name {
-        builder.header("groupName", group_name)
+        builder.param("groupName", group_name)
     } else {
         builder
     };
@@ -48,7 +48,7 @@ pub fn get_license(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct License {
     pub id: i32,
@@ -64,13 +64,14 @@ pub struct License {
 
 #[cfg(test)]
 mod test {
-    use crate::auth::test::create_test_fossology_with_writetoken;
+    use crate::testing::MockFossology;
 
     use super::*;
 
     #[test]
     fn get_correct_license() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
         let mit = get_license(&fossology, "MIT", None).unwrap();
 
@@ -79,10 +80,32 @@ mod test {
 
     #[test]
     fn error_on_invalid_license() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
         let err = get_license(&fossology, "does_not_exist", None).unwrap_err();
 
         assert!(err.to_string().contains("No license found"));
     }
+
+    #[test]
+    fn license_is_retrieved_with_path_parameter() {
+        let mock = MockFossology::with_version("1.3.0");
+        let fossology = mock.fossology();
+
+        let mit = get_license(&fossology, "MIT", None).unwrap();
+        assert_eq!(mit.full_name, "MIT License");
+        assert_eq!(mit.is_candidate, Some(false));
+    }
+
+    #[test]
+    fn license_is_retrieved_with_header_before_1_3_0() {
+        let mock = MockFossology::with_version("1.2.0");
+        mock.add_license("Apache-2.0", "Apache License 2.0", "Apache License");
+        let fossology = mock.fossology();
+
+        let apache = get_license(&fossology, "Apache-2.0", None).unwrap();
+        assert_eq!(apache.full_name, "Apache License 2.0");
+        assert_eq!(apache.is_candidate, None);
+    }
 }
@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Generate and download reports.
+
+use std::io::Write;
+
+use reqwest::StatusCode;
+
+use crate::{copy_response_to, version::Capability, Fossology, FossologyError, FossologyResponse};
+
+/// Schedule generation of a report for the upload. Returns the id of the report.
+///
+/// # Errors
+///
+/// - Reports are not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Info`](crate::Info).
+/// - Response doesn't contain the id of the report.
+pub fn generate_report(
+    fossology: &Fossology,
+    upload_id: i32,
+    report_format: ReportFormat,
+    group_name: Option<&str>,
+) -> Result<i32, FossologyError> {
+    fossology.require(Capability::Report)?;
+
+    let mut builder = fossology
+        .init_get_with_token("report")
+        .param("uploadId", upload_id.to_string())
+        .param("reportFormat", report_format.as_str());
+
+    builder = if let Some(group_name) = group_name {
+        builder.param("groupName", group_name)
+    } else {
+        builder
+    };
+
+    let response = builder.send()?.json::<FossologyResponse<crate::Info>>()?;
+
+    match response {
+        // The message of a successful response is the URL of the report.
+        FossologyResponse::Response(res) => res
+            .message
+            .rsplit('/')
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or(FossologyError::UnexpectedResponse(res.message)),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Download the generated report into `writer`.
+///
+/// Returns the number of bytes written, or `None` if the report is not ready yet.
+///
+/// # Errors
+///
+/// - Reports are not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response is not successful.
+/// - Writing to `writer` fails.
+pub fn download_report<W: Write + ?Sized>(
+    fossology: &Fossology,
+    report_id: i32,
+    writer: &mut W,
+) -> Result<Option<u64>, FossologyError> {
+    fossology.require(Capability::Report)?;
+
+    let response = fossology
+        .init_get_with_token(&format!("report/{}", report_id))
+        .send()?;
+
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+        return Ok(None);
+    }
+
+    copy_response_to(response, writer).map(Some)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Dep5,
+    Spdx2,
+    Spdx2Tv,
+    ReadmeOss,
+    UnifiedReport,
+    CliXml,
+}
+
+impl ReportFormat {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Dep5 => "dep5",
+            Self::Spdx2 => "spdx2",
+            Self::Spdx2Tv => "spdx2tv",
+            Self::ReadmeOss => "readmeoss",
+            Self::UnifiedReport => "unifiedreport",
+            Self::CliXml => "clixml",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{testing::MockFossology, upload::new_upload_from_file};
+
+    use super::*;
+
+    #[test]
+    fn download_spdx_report() {
+        let mock = MockFossology::start();
+        mock.set_processing_polls(2);
+        let fossology = mock.fossology();
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+
+        let report_id =
+            generate_report(&fossology, upload.upload_id, ReportFormat::Spdx2Tv, None).unwrap();
+
+        let mut report = Vec::new();
+        let mut polls = 0;
+        while download_report(&fossology, report_id, &mut report)
+            .unwrap()
+            .is_none()
+        {
+            polls += 1;
+        }
+
+        assert_eq!(polls, 2);
+        assert!(String::from_utf8(report).unwrap().contains("SPDXVersion"));
+    }
+}
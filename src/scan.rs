@@ -0,0 +1,301 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Scan a local file from start to finish.
+//!
+//! [`scan_file`] hashes the file, uploads it unless an upload of the same file already exists,
+//! waits for the unpacking, schedules the agents, waits for them to finish and returns the
+//! findings of the files in the upload.
+
+use std::{path::Path, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    job::{iter_jobs, schedule_analysis, wait_for_jobs, Job, JobFilter, ScheduleAgents},
+    upload::{find_upload_by_sha256, new_upload_from_file, tree::walk, Findings, Hash},
+    version::Capability,
+    Fossology, FossologyError,
+};
+
+/// Options for [`scan_file`].
+#[derive(Debug)]
+pub struct ScanOptions {
+    /// Folder of new uploads.
+    pub folder_id: i32,
+
+    pub group_name: Option<String>,
+
+    /// Agents scheduled for new uploads.
+    pub agents: ScheduleAgents,
+
+    /// Schedule the agents also when an upload of the file already exists. By default the
+    /// findings of the existing upload are returned as they are.
+    pub rescan_existing: bool,
+
+    /// Time between the polls of the jobs.
+    pub poll_interval: Duration,
+
+    /// Maximum time to wait for each of the unpacking and the agents. `None` waits forever.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            folder_id: 1,
+            group_name: None,
+            agents: ScheduleAgents::quick_license_scan(),
+            rescan_existing: false,
+            poll_interval: Duration::from_secs(5),
+            timeout: None,
+        }
+    }
+}
+
+/// Result of [`scan_file`].
+#[derive(Debug, Serialize)]
+pub struct ScanResult {
+    pub upload_id: i32,
+
+    /// Hashes of the scanned file.
+    pub hash: Hash,
+
+    /// True if the file was uploaded by the scan, false if an existing upload was used.
+    pub uploaded: bool,
+
+    /// Id of the job running the agents, if the agents were scheduled.
+    pub job_id: Option<i32>,
+
+    /// Jobs of the upload after the scan.
+    pub jobs: Vec<Job>,
+
+    /// Files of the upload with their findings. Directories are not included.
+    pub files: Vec<ScannedFile>,
+}
+
+/// File in the scanned upload.
+#[derive(Debug, Serialize)]
+pub struct ScannedFile {
+    /// Path of the file in the upload, separated with `/`.
+    pub path: String,
+
+    pub hash: Option<Hash>,
+
+    pub findings: Option<Findings>,
+}
+
+/// Scan the file and return its findings.
+///
+/// Uploads are identified by the SHA256 of the file, so scanning the same file again uses the
+/// existing upload instead of uploading it again. Jobs of an existing upload that finished before
+/// the scan are not waited for, and their failures don't fail the scan.
+///
+/// # Errors
+///
+/// - Tree view is not supported by the API version, checked before anything is uploaded.
+/// - File can't be read.
+/// - Existing uploads can't be searched or the file can't be uploaded.
+/// - Agents can't be scheduled.
+/// - Jobs fail or don't finish before the timeout, see [`wait_for_jobs`].
+/// - File tree of the upload can't be retrieved.
+pub fn scan_file<P: AsRef<Path>>(
+    fossology: &Fossology,
+    path: P,
+    options: &ScanOptions,
+) -> Result<ScanResult, FossologyError> {
+    fossology.require(Capability::TreeView)?;
+
+    let hash = Hash::from_file(&path)?;
+
+    let existing = match &hash.sha256 {
+        Some(sha256) => {
+            find_upload_by_sha256(fossology, sha256, None, options.group_name.as_deref())?
+        }
+        None => None,
+    };
+    let uploaded = existing.is_none();
+    let (upload_id, folder_id, finished_jobs) = match existing {
+        Some(upload) => (
+            upload.id,
+            upload.folder_id,
+            finished_jobs(fossology, upload.id)?,
+        ),
+        None => (
+            new_upload_from_file(fossology, options.folder_id, &path)?.upload_id,
+            options.folder_id,
+            Vec::new(),
+        ),
+    };
+
+    let mut jobs = wait_for_jobs(
+        fossology,
+        upload_id,
+        &finished_jobs,
+        options.poll_interval,
+        options.timeout,
+    )?;
+
+    let job_id = if uploaded || options.rescan_existing {
+        let existing_jobs = jobs.iter().map(|job| job.id).collect::<Vec<_>>();
+        let job = schedule_analysis(
+            fossology,
+            folder_id,
+            upload_id,
+            options.group_name.clone(),
+            &options.agents,
+        )?;
+        jobs = wait_for_jobs(
+            fossology,
+            upload_id,
+            &existing_jobs,
+            options.poll_interval,
+            options.timeout,
+        )?;
+        Some(job.id)
+    } else {
+        None
+    };
+
+    let mut files = Vec::new();
+    for entry in walk(fossology, upload_id)? {
+        let entry = entry?;
+        if !entry.item.is_directory {
+            files.push(ScannedFile {
+                path: entry.path,
+                hash: entry.item.hash,
+                findings: entry.item.findings,
+            });
+        }
+    }
+
+    Ok(ScanResult {
+        upload_id,
+        hash,
+        uploaded,
+        job_id,
+        jobs,
+        files,
+    })
+}
+
+/// Ids of the jobs of the upload that have already finished.
+fn finished_jobs(fossology: &Fossology, upload_id: i32) -> Result<Vec<i32>, FossologyError> {
+    let filter = JobFilter {
+        upload_id: Some(upload_id),
+        ..JobFilter::default()
+    };
+
+    let mut finished = Vec::new();
+    for job in iter_jobs(fossology, &filter) {
+        let job = job?;
+        if job.status.is_finished() {
+            finished.push(job.id);
+        }
+    }
+
+    Ok(finished)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{testing::MockFossology, upload::new_upload_from_file};
+
+    use super::*;
+
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
+
+    fn options() -> ScanOptions {
+        ScanOptions {
+            poll_interval: Duration::from_millis(10),
+            timeout: Some(Duration::from_secs(10)),
+            ..ScanOptions::default()
+        }
+    }
+
+    #[test]
+    fn file_is_uploaded_once() {
+        let mock = MockFossology::start();
+        mock.set_processing_polls(2);
+        let fossology = mock.fossology();
+
+        let first = scan_file(&fossology, ARCHIVE, &options()).unwrap();
+        assert!(first.uploaded);
+        assert!(first.job_id.is_some());
+        assert_eq!(first.jobs.len(), 2);
+        assert_eq!(first.files.len(), 1);
+        assert_eq!(first.files[0].path, "base-files_11.tar.xz");
+
+        let second = scan_file(&fossology, ARCHIVE, &options()).unwrap();
+        assert!(!second.uploaded);
+        assert_eq!(second.upload_id, first.upload_id);
+        assert!(second.job_id.is_none());
+        assert_eq!(mock.upload_ids(), vec![first.upload_id]);
+
+        let rescan = ScanOptions {
+            rescan_existing: true,
+            ..options()
+        };
+        let third = scan_file(&fossology, ARCHIVE, &rescan).unwrap();
+        assert_eq!(third.upload_id, first.upload_id);
+        assert_eq!(third.jobs.len(), 3);
+    }
+
+    #[test]
+    fn failed_agents_fail_the_scan() {
+        let mock = MockFossology::start();
+        mock.fail_agent("nomos");
+        let fossology = mock.fossology();
+
+        match scan_file(&fossology, ARCHIVE, &options()) {
+            Err(FossologyError::JobFailed { agents, .. }) => {
+                assert_eq!(agents, vec!["nomos (Failed)".to_string()]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn earlier_failures_of_existing_upload_are_ignored() {
+        let mock = MockFossology::start();
+        mock.fail_agent("nomos");
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 2, ARCHIVE).unwrap();
+        let mut agents = ScheduleAgents::default();
+        agents.analysis.nomos = true;
+        let failed = schedule_analysis(&fossology, 2, upload.upload_id, None, &agents).unwrap();
+
+        let rescan = ScanOptions {
+            folder_id: 1,
+            agents: ScheduleAgents::builder()
+                .copyright_email_author()
+                .build()
+                .unwrap(),
+            rescan_existing: true,
+            ..options()
+        };
+        let result = scan_file(&fossology, ARCHIVE, &rescan).unwrap();
+
+        assert!(!result.uploaded);
+        assert_eq!(result.upload_id, upload.upload_id);
+        assert!(result.jobs.iter().any(|job| job.id == failed.id));
+        assert_eq!(mock.upload_ids(), vec![upload.upload_id]);
+    }
+
+    #[test]
+    fn scan_requires_tree_view() {
+        let mock = MockFossology::with_version("1.5.0");
+        let fossology = mock.fossology();
+
+        assert!(matches!(
+            scan_file(&fossology, ARCHIVE, &options()),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::TreeView,
+                ..
+            })
+        ));
+        assert!(mock.upload_ids().is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Search for files in the uploads.
+
+use serde::Deserialize;
+
+use crate::{
+    total_pages, upload::Upload, version::Capability, Fossology, FossologyError, FossologyResponse,
+    Page, Paginator,
+};
+
+/// Get one page of files matching the search parameters.
+///
+/// # Errors
+///
+/// - Search is not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Vec`] of [`SearchResult`]s or [`Info`](crate::Info).
+/// - Response is not [`Vec`] of [`SearchResult`]s.
+pub fn search(
+    fossology: &Fossology,
+    params: &SearchParameters,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<SearchPage, FossologyError> {
+    fossology.require(Capability::Search)?;
+
+    let mut builder = fossology
+        .init_get_with_token("search")
+        .param("searchType", params.search_type.as_str());
+
+    let filters = [
+        ("filename", params.filename.clone()),
+        ("tag", params.tag.clone()),
+        (
+            "filesizemin",
+            params.filesize_min.map(|size| size.to_string()),
+        ),
+        (
+            "filesizemax",
+            params.filesize_max.map(|size| size.to_string()),
+        ),
+        ("license", params.license.clone()),
+        ("copyright", params.copyright.clone()),
+        ("uploadId", params.upload_id.map(|id| id.to_string())),
+        ("groupName", params.group_name.clone()),
+        ("limit", limit.map(|limit| limit.to_string())),
+        ("page", page.map(|page| page.to_string())),
+    ];
+
+    for (name, value) in filters {
+        if let Some(value) = value {
+            builder = builder.param(name, value);
+        }
+    }
+
+    let response = builder.send()?;
+
+    let total_pages = total_pages(&response);
+
+    let response = response.json::<FossologyResponse<Vec<SearchResult>>>()?;
+
+    match response {
+        FossologyResponse::Response(items) => Ok(SearchPage { items, total_pages }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Iterate over all files matching the search parameters, retrieving the results page by page.
+pub fn search_all<'a>(fossology: &'a Fossology, params: &'a SearchParameters) -> SearchIter<'a> {
+    Paginator::new(move |limit, page| search(fossology, params, Some(limit), Some(page)))
+}
+
+/// Filters for [`search`]. Filters that are `None` are not used.
+#[derive(Debug, Default, Clone)]
+pub struct SearchParameters {
+    pub search_type: SearchType,
+
+    /// Filename pattern, `%` matches any characters.
+    pub filename: Option<String>,
+
+    pub tag: Option<String>,
+
+    /// Minimum size of the file in bytes.
+    pub filesize_min: Option<i64>,
+
+    /// Maximum size of the file in bytes.
+    pub filesize_max: Option<i64>,
+
+    /// Short name of a license found in the file.
+    pub license: Option<String>,
+
+    /// Copyright statement found in the file.
+    pub copyright: Option<String>,
+
+    /// Limit the search to the upload.
+    pub upload_id: Option<i32>,
+
+    pub group_name: Option<String>,
+}
+
+/// Kind of items to search for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchType {
+    #[default]
+    AllFiles,
+    Containers,
+    Directory,
+}
+
+impl SearchType {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::AllFiles => "allfiles",
+            Self::Containers => "containers",
+            Self::Directory => "directory",
+        }
+    }
+}
+
+/// Page of search results.
+pub type SearchPage = Page<SearchResult>;
+
+/// File matching the search.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    /// Upload containing the file.
+    pub upload: Upload,
+
+    /// Id of the file in the upload's file tree.
+    pub upload_tree_id: i32,
+
+    /// Name of the file.
+    pub filename: String,
+}
+
+impl SearchResult {
+    pub const fn upload_id(&self) -> i32 {
+        self.upload.id
+    }
+}
+
+/// Iterator over all results of a search. Created with [`search_all`].
+pub type SearchIter<'a> = Paginator<'a, SearchResult>;
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        testing::MockFossology,
+        upload::{new_upload_from_bytes, new_upload_from_file},
+    };
+
+    use super::*;
+
+    #[test]
+    fn search_by_filename() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+
+        let params = SearchParameters {
+            filename: Some("base-files%".to_string()),
+            ..SearchParameters::default()
+        };
+
+        let results = search_all(&fossology, &params)
+            .page_size(2)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert!(results
+            .iter()
+            .any(|result| result.upload_id() == upload.upload_id
+                && result.filename == "base-files_11.tar.xz"));
+    }
+
+    #[test]
+    fn search_results_are_paginated_and_filtered() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let mut uploads = Vec::new();
+        for name in ["first.tar.gz", "second.tar.gz", "third.zip"] {
+            let upload = new_upload_from_bytes(&fossology, 1, name, name.as_bytes().to_vec());
+            uploads.push(upload.unwrap().upload_id);
+        }
+
+        let params = SearchParameters {
+            filename: Some("%.tar.gz".to_string()),
+            ..SearchParameters::default()
+        };
+
+        let page = search(&fossology, &params, Some(1), Some(2)).unwrap();
+        assert_eq!(page.total_pages, Some(2));
+        assert_eq!(page.items[0].filename, "second.tar.gz");
+
+        let results = search_all(&fossology, &params)
+            .page_size(1)
+            .map(|result| result.map(|result| result.upload_id()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results, uploads[..2]);
+
+        let in_upload = SearchParameters {
+            upload_id: Some(uploads[2]),
+            ..SearchParameters::default()
+        };
+        let results = search_all(&fossology, &in_upload)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].filename, "third.zip");
+
+        let large = SearchParameters {
+            filesize_min: Some(100),
+            ..SearchParameters::default()
+        };
+        assert_eq!(search_all(&fossology, &large).count(), 0);
+    }
+}
@@ -0,0 +1,1199 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! In-process mock of Fossology's REST API for tests.
+//!
+//! [`MockFossology`] serves the endpoints supported by the crate from memory, so code using the
+//! crate can be tested without a running Fossology instance.
+//!
+//! ```no_run
+//! use fossology_rs::{testing::MockFossology, upload::new_upload_from_file};
+//!
+//! let mock = MockFossology::start();
+//! let fossology = mock.fossology();
+//!
+//! let upload = new_upload_from_file(&fossology, 1, "archive.tar.xz").unwrap();
+//! assert!(mock.upload_ids().contains(&upload.upload_id));
+//! ```
+
+use std::{
+    convert::TryFrom,
+    io::BufReader,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use crate::{
+    upload::Hash,
+    version::{ApiVersion, Capability},
+    Fossology,
+};
+
+use self::http::{read_request, write_response, Request, Response};
+
+mod http;
+
+/// API version served by [`MockFossology::start`].
+pub const LATEST_VERSION: &str = "2.0.0";
+
+/// Token that is always accepted by the mock server.
+const DEFAULT_TOKEN: &str = "mock-token";
+
+/// Group of the user of the mock server.
+const MOCK_GROUP_ID: i32 = 3;
+
+/// Permissions that can be given to uploads.
+const PERMISSIONS: [&str; 5] = ["none", "read_only", "read_write", "clearing_admin", "admin"];
+
+/// Statuses that jobs can be filtered by.
+const JOB_STATUSES: [&str; 6] = [
+    "Completed",
+    "Failed",
+    "Queued",
+    "Processing",
+    "Killed",
+    "Paused",
+];
+
+/// Mock Fossology server listening on a random local port.
+///
+/// The server is stopped when the value is dropped.
+pub struct MockFossology {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockFossology {
+    /// Start a server for the latest supported API version.
+    ///
+    /// # Panics
+    ///
+    /// - Server can't bind to a local port.
+    pub fn start() -> Self {
+        Self::with_version(LATEST_VERSION)
+    }
+
+    /// Start a server that reports the given API version and only serves the endpoints that
+    /// exist in that version.
+    ///
+    /// # Panics
+    ///
+    /// - Server can't bind to a local port.
+    /// - Version is not a valid [`ApiVersion`].
+    pub fn with_version(version: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("mock server can't bind");
+        let address = listener.local_addr().expect("mock server has no address");
+        let state = Arc::new(Mutex::new(State::new(version)));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = Arc::clone(&state);
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let state = Arc::clone(&state);
+                        thread::spawn(move || handle_connection(stream, &state));
+                    }
+                }
+            })
+        };
+
+        Self {
+            address,
+            state,
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// Base URI of the API, like `http://127.0.0.1:1234/repo/api/v1`. Versions from 2.0.0 are
+    /// served at `/repo/api/v2`.
+    pub fn uri(&self) -> String {
+        format!("http://{}{}", self.address, self.state().base_path())
+    }
+
+    /// Token accepted by the server without creating it through the API.
+    pub const fn token(&self) -> &'static str {
+        DEFAULT_TOKEN
+    }
+
+    /// Client connected to the server with a valid token.
+    ///
+    /// # Panics
+    ///
+    /// - Client can't be created.
+    pub fn fossology(&self) -> Fossology {
+        Fossology::new(&self.uri(), DEFAULT_TOKEN).expect("client for mock server")
+    }
+
+    /// Number of times the status of new jobs is reported as `Processing` before they are
+    /// `Completed`. Defaults to 0.
+    pub fn set_processing_polls(&self, polls: u32) {
+        self.state().processing_polls = polls;
+    }
+
+    /// Make the runs of the agent, like `nomos`, fail. Jobs that run the agent are `Failed`.
+    pub fn fail_agent(&self, agent: &str) {
+        self.state().failing_agents.push(agent.to_string());
+    }
+
+    /// Add a license to the licenses served by the server. `MIT` exists by default.
+    pub fn add_license(&self, short_name: &str, full_name: &str, text: &str) {
+        let mut state = self.state();
+        let id = state.next_id();
+        state.licenses.push(MockLicense {
+            id,
+            short_name: short_name.to_string(),
+            full_name: full_name.to_string(),
+            text: text.to_string(),
+        });
+    }
+
+    /// Ids of the uploads on the server.
+    pub fn upload_ids(&self) -> Vec<i32> {
+        self.state()
+            .uploads
+            .iter()
+            .map(|upload| upload.id)
+            .collect()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockFossology {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the listener so it notices the shutdown.
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct State {
+    version: String,
+    api_version: ApiVersion,
+    ids: i32,
+    tokens: Vec<String>,
+    read_only_tokens: Vec<String>,
+    processing_polls: u32,
+    failing_agents: Vec<String>,
+    uploads: Vec<MockUpload>,
+    jobs: Vec<MockJob>,
+    licenses: Vec<MockLicense>,
+    reports: Vec<MockReport>,
+}
+
+struct MockUpload {
+    id: i32,
+    top_item: i32,
+    folder_id: i32,
+    name: String,
+    date: String,
+    hash: Hash,
+    content: Vec<u8>,
+    public_perm: String,
+
+    /// Ids of the groups with access to the upload and their permissions.
+    group_perms: Vec<(i32, String)>,
+}
+
+struct MockJob {
+    id: i32,
+    name: String,
+    upload_id: i32,
+    date: String,
+    remaining_polls: u32,
+    agents: Vec<String>,
+
+    /// Status set by killing or pausing the job.
+    control_status: Option<&'static str>,
+}
+
+struct MockLicense {
+    id: i32,
+    short_name: String,
+    full_name: String,
+    text: String,
+}
+
+struct MockReport {
+    id: i32,
+    upload_id: i32,
+    format: String,
+    remaining_polls: u32,
+}
+
+impl State {
+    fn new(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+            api_version: version.parse().expect("Invalid API version"),
+            ids: 0,
+            tokens: vec![DEFAULT_TOKEN.to_string()],
+            read_only_tokens: Vec::new(),
+            processing_polls: 0,
+            failing_agents: Vec::new(),
+            uploads: Vec::new(),
+            jobs: Vec::new(),
+            licenses: vec![MockLicense {
+                id: 1,
+                short_name: "MIT".to_string(),
+                full_name: "MIT License".to_string(),
+                text: "Permission is hereby granted, free of charge, ...".to_string(),
+            }],
+            reports: Vec::new(),
+        }
+    }
+
+    const fn next_id(&mut self) -> i32 {
+        self.ids += 1;
+        self.ids
+    }
+
+    fn supports(&self, capability: Capability) -> bool {
+        capability.is_supported_by(self.api_version)
+    }
+
+    const fn base_path(&self) -> &'static str {
+        if self.api_version.major >= 2 {
+            "/repo/api/v2"
+        } else {
+            "/repo/api/v1"
+        }
+    }
+
+    /// Parameters are headers in API v1 and query parameters in API v2.
+    fn param<'a>(&self, request: &'a Request, name: &str) -> Option<&'a str> {
+        if self.supports(Capability::ApiV2) {
+            request.query(name)
+        } else {
+            request.header(name)
+        }
+    }
+
+    fn add_job(&mut self, name: &str, upload_id: i32, agents: Vec<String>) -> i32 {
+        let id = self.next_id();
+        self.jobs.push(MockJob {
+            id,
+            name: name.to_string(),
+            upload_id,
+            date: now(),
+            remaining_polls: self.processing_polls,
+            agents,
+            control_status: None,
+        });
+        id
+    }
+}
+
+fn now() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M:%S%.f%:z").to_string()
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(_) => return,
+    });
+    let mut writer = stream;
+
+    if let Ok(Some(request)) = read_request(&mut reader) {
+        let response = {
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            route(&mut state, &request)
+        };
+        let _ = write_response(&mut writer, &response);
+    }
+}
+
+fn route(state: &mut State, request: &Request) -> Response {
+    let path = match request
+        .path
+        .strip_prefix(state.base_path())
+        .and_then(|path| path.strip_prefix('/'))
+    {
+        Some(path) => path.trim_end_matches('/'),
+        None => return not_found(),
+    };
+    let segments = path.split('/').collect::<Vec<_>>();
+    let method = request.method.as_str();
+
+    if let Some(response) = route_public(state, request, method, &segments) {
+        return response;
+    }
+
+    if !request
+        .bearer_token()
+        .is_some_and(|token| state.tokens.iter().any(|t| t == token))
+    {
+        return Response::info(403, "Invalid or missing token");
+    }
+
+    if method != "GET"
+        && request
+            .bearer_token()
+            .is_some_and(|token| state.read_only_tokens.iter().any(|t| t == token))
+    {
+        return Response::info(403, "Insufficient permissions for the token");
+    }
+
+    match segments.as_slice() {
+        ["uploads", rest @ ..] => route_uploads(state, request, method, rest),
+        ["jobs", rest @ ..] => route_jobs(state, request, method, rest),
+        _ => route_other(state, request, method, &segments),
+    }
+}
+
+/// Endpoints that don't need a token.
+fn route_public(
+    state: &mut State,
+    request: &Request,
+    method: &str,
+    segments: &[&str],
+) -> Option<Response> {
+    match (method, segments) {
+        ("POST", ["tokens"]) => Some(create_token(state, request)),
+        ("GET", ["version"]) => Some(Response::json(
+            200,
+            &json!({ "version": state.version, "security": ["bearerAuth"] }),
+        )),
+        ("GET", ["info"]) if state.supports(Capability::ApiInfo) => Some(info(state)),
+        ("GET", ["health"]) if state.supports(Capability::Health) => Some(Response::json(
+            200,
+            &json!({ "status": "OK", "scheduler": { "status": "OK" }, "db": { "status": "OK" } }),
+        )),
+        _ => None,
+    }
+}
+
+/// Endpoints below `uploads`.
+fn route_uploads(state: &mut State, request: &Request, method: &str, path: &[&str]) -> Response {
+    match (method, path) {
+        ("POST", []) => create_upload(state, request),
+        ("GET", []) => get_uploads(state, request),
+        ("GET", [id]) => with_upload(state, id, |upload| upload_json(state, upload)),
+        ("GET", [id, "topitem"]) => {
+            with_upload(state, id, |upload| Response::info(200, upload.top_item))
+        }
+        ("GET", [id, "download"]) => with_upload(state, id, |upload| {
+            Response::bytes(200, upload.content.clone())
+        }),
+        ("GET", [id, "item", item_id, "tree", "view"]) if state.supports(Capability::TreeView) => {
+            with_upload(state, id, |upload| tree_view(upload, item_id))
+        }
+        ("GET", [id, "item", item_id, "download"]) if state.supports(Capability::ItemDownload) => {
+            with_upload(state, id, |upload| download_item(upload, item_id))
+        }
+        ("POST", [id, "item", item_id, "bulk-scan"]) if state.supports(Capability::BulkScan) => {
+            bulk_scan(state, id, item_id, request)
+        }
+        ("GET", [id, "perm-groups"]) if state.supports(Capability::UploadPermissions) => {
+            with_upload(state, id, upload_permissions)
+        }
+        ("PUT", [id, "permissions"]) if state.supports(Capability::UploadPermissions) => {
+            set_upload_permissions(state, id, request)
+        }
+        _ => not_found(),
+    }
+}
+
+/// Endpoints below `jobs`.
+fn route_jobs(state: &mut State, request: &Request, method: &str, path: &[&str]) -> Response {
+    match (method, path) {
+        ("GET", []) => get_jobs(state, request),
+        ("GET", ["all"]) if state.supports(Capability::AllJobs) => get_jobs(state, request),
+        ("GET", [id]) => get_job(state, id),
+        ("DELETE", [id]) if state.supports(Capability::JobControl) => kill_job(state, id, None),
+        ("DELETE", [id, queue_id]) if state.supports(Capability::JobControl) => {
+            kill_job(state, id, Some(queue_id))
+        }
+        ("PATCH", [id]) if state.supports(Capability::JobControl) => change_job(state, id, request),
+        ("POST", []) => schedule_jobs(state, request),
+        _ => not_found(),
+    }
+}
+
+/// Search, report and license endpoints.
+fn route_other(state: &mut State, request: &Request, method: &str, segments: &[&str]) -> Response {
+    match (method, segments) {
+        ("POST", ["filesearch"]) => filesearch(state, request),
+        ("GET", ["search"]) if state.supports(Capability::Search) => search(state, request),
+        ("GET", ["report"]) if state.supports(Capability::Report) => {
+            generate_report(state, request)
+        }
+        ("GET", ["report", id]) if state.supports(Capability::Report) => download_report(state, id),
+        ("GET", ["license"]) if !state.supports(Capability::LicensePathParam) => {
+            license(state, request.header("shortName").unwrap_or_default())
+        }
+        ("GET", ["license", short_name]) if state.supports(Capability::LicensePathParam) => {
+            license(state, short_name)
+        }
+        _ => not_found(),
+    }
+}
+
+fn not_found() -> Response {
+    Response::info(404, "Resource not found")
+}
+
+fn create_token(state: &mut State, request: &Request) -> Response {
+    let params: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    if params["username"] != "fossy" || params["password"] != "fossy" {
+        return Response::info(404, "Username or password is incorrect");
+    }
+
+    let token = format!("mock-token-{}", state.next_id());
+    state.tokens.push(token.clone());
+    let scope = params
+        .get("token_scope")
+        .or_else(|| params.get("tokenScope"));
+    if scope.and_then(Value::as_str) == Some("read") {
+        state.read_only_tokens.push(token.clone());
+    }
+
+    Response::json(
+        201,
+        &json!({ "Authorization": format!("Bearer {}", token) }),
+    )
+}
+
+fn info(state: &State) -> Response {
+    Response::json(
+        200,
+        &json!({
+            "name": "FOSSology API",
+            "description": "Automate your fossology instance using REST API",
+            "version": state.version,
+            "security": ["bearerAuth"],
+            "contact": "fossology@fossology.org",
+            "license": {
+                "name": "GPL-2.0-only",
+                "url": "https://github.com/fossology/fossology/blob/master/LICENSE"
+            }
+        }),
+    )
+}
+
+fn create_upload(state: &mut State, request: &Request) -> Response {
+    let Some(folder_id) = state
+        .param(request, "folderId")
+        .and_then(|id| id.parse().ok())
+    else {
+        return Response::info(400, "folderId should be an integer!");
+    };
+
+    let (name, content) = match state.param(request, "uploadType") {
+        Some("url" | "vcs" | "server") => {
+            let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+            let name = body["name"]
+                .as_str()
+                .or_else(|| body["vcsName"].as_str())
+                .or_else(|| body["url"].as_str())
+                .or_else(|| body["vcsUrl"].as_str())
+                .unwrap_or_default()
+                .to_string();
+            (name, Vec::new())
+        }
+        _ => match request
+            .multipart()
+            .into_iter()
+            .find(|(name, _, _)| name == "fileInput")
+        {
+            Some((_, filename, content)) => (filename.unwrap_or_default(), content),
+            None => return Response::info(400, "No file selected for upload"),
+        },
+    };
+
+    let id = state.next_id();
+    let top_item = state.next_id();
+    let hash = Hash::from_reader(content.as_slice()).unwrap_or_default();
+    state.uploads.push(MockUpload {
+        id,
+        top_item,
+        folder_id,
+        name,
+        date: now(),
+        hash,
+        content,
+        public_perm: "none".to_string(),
+        group_perms: vec![(MOCK_GROUP_ID, "admin".to_string())],
+    });
+    state.add_job(
+        "ununpack",
+        id,
+        vec!["ununpack".to_string(), "adj2nest".to_string()],
+    );
+
+    Response::info(201, id)
+}
+
+fn with_upload(state: &State, id: &str, f: impl Fn(&MockUpload) -> Response) -> Response {
+    match state
+        .uploads
+        .iter()
+        .find(|upload| id.parse() == Ok(upload.id))
+    {
+        Some(upload) => f(upload),
+        None => Response::info(404, "Upload does not exist"),
+    }
+}
+
+fn get_uploads(state: &State, request: &Request) -> Response {
+    let folder_id = request
+        .query("folderId")
+        .and_then(|id| id.parse::<i32>().ok());
+    let name = request.query("name").map(str::to_lowercase);
+    let limit = state
+        .param(request, "limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .filter(|limit| *limit > 0);
+    let page = state
+        .param(request, "page")
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    let mut uploads = state
+        .uploads
+        .iter()
+        .filter(|upload| folder_id.is_none_or(|id| upload.folder_id == id))
+        .filter(|upload| {
+            name.as_ref()
+                .is_none_or(|name| upload.name.to_lowercase().contains(name))
+        })
+        .collect::<Vec<_>>();
+
+    let total_pages = limit.map_or(1, |limit| uploads.len().div_ceil(limit).max(1));
+    if let Some(limit) = limit {
+        uploads = uploads
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+    }
+
+    let uploads = uploads
+        .into_iter()
+        .map(|upload| upload_value(state, upload))
+        .collect::<Vec<_>>();
+
+    let mut response = Response::json(200, &Value::Array(uploads));
+    response
+        .headers
+        .push(("X-Total-Pages".to_string(), total_pages.to_string()));
+    response
+}
+
+fn upload_json(state: &State, upload: &MockUpload) -> Response {
+    Response::json(200, &upload_value(state, upload))
+}
+
+fn upload_value(state: &State, upload: &MockUpload) -> Value {
+    if state.supports(Capability::ApiV2) {
+        return json!({
+            "folderId": upload.folder_id,
+            "folderName": "Software Repository",
+            "id": upload.id,
+            "description": "",
+            "uploadName": upload.name,
+            "uploadDate": upload.date,
+            "assignee": null,
+            "hash": upload.hash,
+        });
+    }
+
+    json!({
+        "folderid": upload.folder_id,
+        "foldername": "Software Repository",
+        "id": upload.id,
+        "description": "",
+        "uploadname": upload.name,
+        "uploaddate": upload.date,
+        "assignee": null,
+        "hash": upload.hash,
+    })
+}
+
+/// The uploaded file is the only child of the top level item.
+fn tree_view(upload: &MockUpload, item_id: &str) -> Response {
+    if item_id.parse() != Ok(upload.top_item) {
+        return Response::json(200, &json!([]));
+    }
+
+    Response::json(
+        200,
+        &json!([{
+            "itemId": upload.top_item * 10,
+            "fileName": upload.name,
+            "isDirectory": false,
+            "hash": upload.hash,
+            "findings": { "scanner": [], "conclusion": [], "copyright": [] },
+        }]),
+    )
+}
+
+/// The uploaded file is the only file of the upload.
+fn download_item(upload: &MockUpload, item_id: &str) -> Response {
+    if item_id.parse() == Ok(upload.top_item * 10) {
+        Response::bytes(200, upload.content.clone())
+    } else {
+        Response::info(404, "Item does not exist")
+    }
+}
+
+fn bulk_scan(state: &mut State, id: &str, item_id: &str, request: &Request) -> Response {
+    let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    if !body["refText"].is_string() || !body["bulkActions"].is_array() {
+        return Response::info(400, "Invalid request body");
+    }
+    let Some(upload) = state
+        .uploads
+        .iter()
+        .find(|upload| id.parse() == Ok(upload.id))
+    else {
+        return Response::info(404, "Upload does not exist");
+    };
+    if item_id.parse() != Ok(upload.top_item) && item_id.parse() != Ok(upload.top_item * 10) {
+        return Response::info(404, "Item does not exist");
+    }
+
+    let upload_id = upload.id;
+    let job_id = state.add_job("monkbulk", upload_id, vec!["monkbulk".to_string()]);
+
+    Response::info(201, job_id)
+}
+
+fn upload_permissions(upload: &MockUpload) -> Response {
+    let groups = upload
+        .group_perms
+        .iter()
+        .map(|(group_id, perm)| {
+            json!({
+                "perm": perm,
+                "group_pk": group_id,
+                "group_name": if *group_id == MOCK_GROUP_ID {
+                    "fossy".to_string()
+                } else {
+                    format!("group-{}", group_id)
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Response::json(
+        200,
+        &json!({ "publicPerm": upload.public_perm, "permGroups": groups }),
+    )
+}
+
+fn set_upload_permissions(state: &mut State, id: &str, request: &Request) -> Response {
+    let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    let Some(folder_id) = body["folderId"].as_i64() else {
+        return Response::info(400, "folderId is required");
+    };
+    let perm = |name: &str| match body.get(name) {
+        None => Ok(None),
+        Some(perm) => perm
+            .as_str()
+            .filter(|perm| PERMISSIONS.contains(perm))
+            .map(|perm| Some(perm.to_string()))
+            .ok_or_else(|| Response::info(400, format!("Invalid {}", name))),
+    };
+    let (public_perm, new_perm) = match (perm("publicPermission"), perm("newPermission")) {
+        (Ok(public_perm), Ok(new_perm)) => (public_perm, new_perm),
+        (Err(response), _) | (_, Err(response)) => return response,
+    };
+    let group_id = body["groupId"]
+        .as_i64()
+        .and_then(|id| i32::try_from(id).ok());
+    let all_uploads = body["allUploadsPermission"].as_bool().unwrap_or(false);
+
+    let Some(upload) = state
+        .uploads
+        .iter()
+        .find(|upload| id.parse() == Ok(upload.id))
+    else {
+        return Response::info(404, "Upload does not exist");
+    };
+    if i64::from(upload.folder_id) != folder_id {
+        return Response::info(400, "Upload is not in the folder");
+    }
+    let upload_id = upload.id;
+
+    for upload in state.uploads.iter_mut().filter(|upload| {
+        if all_uploads {
+            i64::from(upload.folder_id) == folder_id
+        } else {
+            upload.id == upload_id
+        }
+    }) {
+        if let Some(public_perm) = &public_perm {
+            upload.public_perm.clone_from(public_perm);
+        }
+        if let (Some(group_id), Some(new_perm)) = (group_id, &new_perm) {
+            upload.group_perms.retain(|(id, _)| *id != group_id);
+            if new_perm != "none" {
+                upload.group_perms.push((group_id, new_perm.clone()));
+            }
+        }
+    }
+
+    Response::info(202, "Permissions updated successfully")
+}
+
+fn get_jobs(state: &mut State, request: &Request) -> Response {
+    if let Some(status) = request.query("status") {
+        if !JOB_STATUSES.contains(&status) {
+            return Response::info(400, format!("Invalid status {}", status));
+        }
+    }
+    let upload_id = request
+        .query("upload")
+        .and_then(|id| id.parse::<i32>().ok());
+    let limit = state
+        .param(request, "limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .filter(|limit| *limit > 0);
+    let page = state
+        .param(request, "page")
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+
+    let failing_agents = state.failing_agents.clone();
+    let mut jobs = state
+        .jobs
+        .iter_mut()
+        .filter(|job| upload_id.is_none_or(|id| job.upload_id == id))
+        .collect::<Vec<_>>();
+
+    let total_pages = limit.map_or(1, |limit| jobs.len().div_ceil(limit).max(1));
+    if let Some(limit) = limit {
+        jobs = jobs
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+    }
+
+    let jobs = jobs
+        .into_iter()
+        .map(|job| job_value(job, &failing_agents))
+        .collect::<Vec<_>>();
+
+    let mut response = Response::json(200, &Value::Array(jobs));
+    response
+        .headers
+        .push(("X-Total-Pages".to_string(), total_pages.to_string()));
+    response
+}
+
+fn get_job(state: &mut State, id: &str) -> Response {
+    let failing_agents = state.failing_agents.clone();
+    match state.jobs.iter_mut().find(|job| id.parse() == Ok(job.id)) {
+        Some(job) => Response::json(200, &job_value(job, &failing_agents)),
+        None => Response::info(404, "Job id doesn't exist"),
+    }
+}
+
+fn kill_job(state: &mut State, id: &str, queue_id: Option<&str>) -> Response {
+    let Some(job) = state.jobs.iter_mut().find(|job| id.parse() == Ok(job.id)) else {
+        return Response::info(404, "Job id doesn't exist");
+    };
+    let queue_exists = queue_id.is_none_or(|queue_id| {
+        queue_id.parse::<i32>().is_ok_and(|queue_id| {
+            (job.id * 100..)
+                .take(job.agents.len())
+                .any(|q| q == queue_id)
+        })
+    });
+    if !queue_exists {
+        return Response::info(404, "Job queue doesn't exist");
+    }
+
+    job.control_status = Some("Killed");
+
+    Response::info(200, "Job killed")
+}
+
+fn change_job(state: &mut State, id: &str, request: &Request) -> Response {
+    let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    let Some(job) = state.jobs.iter_mut().find(|job| id.parse() == Ok(job.id)) else {
+        return Response::info(404, "Job id doesn't exist");
+    };
+
+    match (body["action"].as_str(), job.control_status) {
+        (_, Some("Killed")) => Response::info(400, "Job is killed"),
+        (Some("pause"), _) => {
+            job.control_status = Some("Paused");
+            Response::info(200, "Job paused")
+        }
+        (Some("resume"), Some("Paused")) => {
+            job.control_status = None;
+            Response::info(200, "Job resumed")
+        }
+        (Some("resume"), _) => Response::info(400, "Job is not paused"),
+        (Some("priority"), _) if body["priority"].is_i64() => {
+            Response::info(200, "Priority changed")
+        }
+        _ => Response::info(400, "Invalid action"),
+    }
+}
+
+/// Reports the job as processing until its polls run out.
+fn job_value(job: &mut MockJob, failing_agents: &[String]) -> Value {
+    let processing = job.remaining_polls > 0;
+    if processing {
+        job.remaining_polls -= 1;
+    }
+
+    let job_queue = job
+        .agents
+        .iter()
+        .zip(job.id * 100..)
+        .map(|(agent, queue_id)| {
+            let failed = failing_agents.contains(agent);
+            json!({
+                "jobQueueId": queue_id,
+                "jobQueueType": agent,
+                "startTime": job.date,
+                "endTime": if processing { Value::Null } else { json!(job.date) },
+                "status": match (processing, failed) {
+                    (true, _) => "Started",
+                    (false, true) => "Failed",
+                    (false, false) => "Completed",
+                },
+                "itemsProcessed": if processing { 0 } else { 42 },
+                "log": format!("/srv/fossology/repository/logs/{}.log", queue_id),
+                "dependencies": [],
+                "itemsPerSec": 0.0,
+                "isInProgress": processing,
+                "isReady": !processing,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let status = if let Some(status) = job.control_status {
+        status
+    } else if processing {
+        "Processing"
+    } else if job
+        .agents
+        .iter()
+        .any(|agent| failing_agents.contains(agent))
+    {
+        "Failed"
+    } else {
+        "Completed"
+    };
+
+    json!({
+        "id": job.id,
+        "name": job.name,
+        "queueDate": job.date,
+        "uploadId": job.upload_id.to_string(),
+        "userId": "3",
+        "groupId": "3",
+        "eta": 0,
+        "status": status,
+        "jobQueue": job_queue,
+    })
+}
+
+fn schedule_jobs(state: &mut State, request: &Request) -> Response {
+    let Some(upload_id) = state
+        .param(request, "uploadId")
+        .and_then(|id| id.parse().ok())
+    else {
+        return Response::info(400, "uploadId should be an integer!");
+    };
+    let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    if state.supports(Capability::ApiV2) && has_snake_case_keys(&body) {
+        return Response::info(400, "Invalid request body");
+    }
+    let Some(upload) = state.uploads.iter().find(|upload| upload.id == upload_id) else {
+        return Response::info(404, "Upload does not exist");
+    };
+    if state
+        .param(request, "folderId")
+        .is_some_and(|folder_id| folder_id != upload.folder_id.to_string())
+    {
+        return Response::info(404, "Upload is not in the folder");
+    }
+
+    // Agents that are enabled in the analysis.
+    let agents = body["analysis"]
+        .as_object()
+        .map(|analysis| {
+            analysis
+                .iter()
+                .filter(|(_, enabled)| match enabled {
+                    Value::Bool(enabled) => *enabled,
+                    Value::Object(options) => options.values().any(|o| o == &Value::Bool(true)),
+                    _ => false,
+                })
+                .map(|(agent, _)| agent.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let id = state.add_job("agents", upload_id, agents);
+
+    Response::info(201, id)
+}
+
+fn filesearch(state: &State, request: &Request) -> Response {
+    let hashes: Vec<Hash> = match serde_json::from_slice(&request.body) {
+        Ok(hashes) => hashes,
+        Err(_) => return Response::info(400, "Invalid request body"),
+    };
+
+    let results = hashes
+        .into_iter()
+        .map(|hash| {
+            let uploads = state
+                .uploads
+                .iter()
+                .filter(|upload| hash.matches(&upload.hash))
+                .collect::<Vec<_>>();
+
+            match uploads.first() {
+                Some(upload) => json!({
+                    "hash": upload.hash,
+                    "findings": { "scanner": [], "conclusion": [], "copyright": [] },
+                    "uploads": uploads.iter().map(|upload| upload.id).collect::<Vec<_>>(),
+                }),
+                None => json!({ "hash": hash, "message": "Not found" }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Response::json(200, &Value::Array(results))
+}
+
+/// Every upload contains one file with the name of the upload. The `filename` is matched like
+/// SQL `LIKE`, where `%` matches any characters.
+fn search(state: &State, request: &Request) -> Response {
+    let param = |name: &str| state.param(request, name);
+    let number = |name: &str| param(name).and_then(|value| value.parse::<i64>().ok());
+    let limit = number("limit")
+        .and_then(|limit| usize::try_from(limit).ok())
+        .filter(|limit| *limit > 0);
+    let page = number("page")
+        .and_then(|page| usize::try_from(page).ok())
+        .unwrap_or(1)
+        .max(1);
+
+    // The mock doesn't have directories, containers or findings.
+    let searches_files = param("searchType").is_none_or(|search_type| search_type == "allfiles");
+    let searches_findings = param("license").is_some() || param("copyright").is_some();
+
+    let mut results = state
+        .uploads
+        .iter()
+        .filter(|_| searches_files && !searches_findings)
+        .filter(|upload| param("filename").is_none_or(|pattern| like(&upload.name, pattern)))
+        .filter(|upload| number("uploadId").is_none_or(|id| i64::from(upload.id) == id))
+        .filter(|upload| {
+            let size = i64::try_from(upload.content.len()).unwrap_or(i64::MAX);
+            number("filesizemin").is_none_or(|min| size >= min)
+                && number("filesizemax").is_none_or(|max| size <= max)
+        })
+        .collect::<Vec<_>>();
+
+    let total_pages = limit.map_or(1, |limit| results.len().div_ceil(limit).max(1));
+    if let Some(limit) = limit {
+        results = results
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+    }
+
+    let results = results
+        .into_iter()
+        .map(|upload| {
+            json!({
+                "upload": upload_value(state, upload),
+                "uploadTreeId": upload.top_item * 10,
+                "filename": upload.name,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut response = Response::json(200, &Value::Array(results));
+    response
+        .headers
+        .push(("X-Total-Pages".to_string(), total_pages.to_string()));
+    response
+}
+
+fn like(text: &str, pattern: &str) -> bool {
+    let parts = pattern.split('%').collect::<Vec<_>>();
+    let (first, rest) = parts.split_first().unwrap_or((&"", &[]));
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    match rest.split_last() {
+        None => remaining.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match remaining.find(part) {
+                    Some(index) => remaining = &remaining[index + part.len()..],
+                    None => return false,
+                }
+            }
+            remaining.ends_with(last)
+        }
+    }
+}
+
+fn generate_report(state: &mut State, request: &Request) -> Response {
+    let Some(upload_id) = state
+        .param(request, "uploadId")
+        .and_then(|id| id.parse().ok())
+    else {
+        return Response::info(400, "uploadId should be an integer!");
+    };
+    let Some(format) = state.param(request, "reportFormat").map(str::to_string) else {
+        return Response::info(400, "reportFormat is missing");
+    };
+    if !state.uploads.iter().any(|upload| upload.id == upload_id) {
+        return Response::info(404, "Upload does not exist");
+    }
+
+    let id = state.next_id();
+    let remaining_polls = state.processing_polls;
+    state.reports.push(MockReport {
+        id,
+        upload_id,
+        format,
+        remaining_polls,
+    });
+
+    Response::info(
+        201,
+        format!("http://localhost{}/report/{}", state.base_path(), id),
+    )
+}
+
+/// The report is not ready until its polls run out.
+fn download_report(state: &mut State, id: &str) -> Response {
+    let Some(report) = state
+        .reports
+        .iter_mut()
+        .find(|report| id.parse() == Ok(report.id))
+    else {
+        return Response::info(404, "Report does not exist");
+    };
+
+    if report.remaining_polls > 0 {
+        report.remaining_polls -= 1;
+        return Response::info(503, "Report is not ready yet");
+    }
+
+    let name = state
+        .uploads
+        .iter()
+        .find(|upload| upload.id == report.upload_id)
+        .map(|upload| upload.name.as_str())
+        .unwrap_or_default();
+    let content = match report.format.as_str() {
+        "spdx2tv" => format!("SPDXVersion: SPDX-2.2\nPackageName: {}\n", name),
+        format => format!("{} report of {}\n", format, name),
+    };
+
+    Response::bytes(200, content.into_bytes())
+}
+
+fn has_snake_case_keys(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .any(|(key, value)| key.contains('_') || has_snake_case_keys(value)),
+        Value::Array(values) => values.iter().any(has_snake_case_keys),
+        _ => false,
+    }
+}
+
+fn license(state: &State, short_name: &str) -> Response {
+    match state
+        .licenses
+        .iter()
+        .find(|license| license.short_name == short_name)
+    {
+        Some(license) => {
+            let mut value = json!({
+                "id": license.id,
+                "shortName": license.short_name,
+                "fullName": license.full_name,
+                "text": license.text,
+                "risk": null,
+            });
+            if state.supports(Capability::LicensePathParam) {
+                value["isCandidate"] = json!(false);
+            }
+            Response::json(200, &value)
+        }
+        None => Response::info(
+            404,
+            format!("No license found with short name '{}'.", short_name),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        auth::{tokens, TokenScope, TokensParameters},
+        job::get_jobs,
+    };
+
+    use super::*;
+
+    #[test]
+    fn token_is_created() {
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "").unwrap();
+        let params = TokensParameters::new(
+            "fossy",
+            "fossy",
+            "test",
+            TokenScope::Write,
+            Utc::now().naive_utc().date(),
+        );
+
+        let token = tokens(&fossology, &params).unwrap();
+        let token = token.authorization.strip_prefix("Bearer ").unwrap();
+
+        let fossology = Fossology::new(&mock.uri(), token).unwrap();
+        assert!(get_jobs(&fossology, None, None, None, None).is_ok());
+    }
+
+    #[test]
+    fn invalid_token_is_rejected() {
+        let mock = MockFossology::start();
+        let fossology = Fossology::new(&mock.uri(), "invalid").unwrap();
+
+        assert!(get_jobs(&fossology, None, None, None, None).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Minimal HTTP/1.1 handling for the mock server.
+
+use std::io::{self, BufRead, Write};
+
+/// Request received by the mock server.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+
+    /// Path without the query string.
+    pub path: String,
+
+    pub query: Vec<(String, String)>,
+
+    pub headers: Vec<(String, String)>,
+
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Value of the header, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Token from the `Authorization` header.
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.header("Authorization")?.strip_prefix("Bearer ")
+    }
+
+    /// Parts of a `multipart/form-data` body as `(name, filename, content)`.
+    pub fn multipart(&self) -> Vec<(String, Option<String>, Vec<u8>)> {
+        let boundary = match self
+            .header("Content-Type")
+            .and_then(|value| value.split("boundary=").nth(1))
+        {
+            Some(boundary) => format!("--{}", boundary.trim_matches('"')),
+            None => return Vec::new(),
+        };
+
+        split(&self.body, boundary.as_bytes())
+            .into_iter()
+            .filter_map(|part| {
+                let part = part.strip_prefix(b"\r\n")?;
+                let header_end = find(part, b"\r\n\r\n")?;
+                let headers = String::from_utf8_lossy(&part[..header_end]);
+                let content = part[header_end + 4..].strip_suffix(b"\r\n")?;
+
+                let disposition = headers
+                    .lines()
+                    .find(|line| line.to_lowercase().starts_with("content-disposition"))?;
+                let name = disposition_value(disposition, "name")?;
+                let filename = disposition_value(disposition, "filename");
+
+                Some((name, filename, content.to_vec()))
+            })
+            .collect()
+    }
+}
+
+/// Response sent by the mock server.
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, body: &serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    pub fn bytes(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "application/octet-stream".to_string(),
+            )],
+            body,
+        }
+    }
+
+    /// Response in the format of Fossology's `Info` object.
+    pub fn info(status: u16, message: impl Into<serde_json::Value>) -> Self {
+        let info_type = if status < 400 { "INFO" } else { "ERROR" };
+        Self::json(
+            status,
+            &serde_json::json!({ "code": status, "message": message.into(), "type": info_type }),
+        )
+    }
+}
+
+/// Read one request from the stream. Returns `None` if the connection was closed.
+pub fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Option<Request>> {
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)),
+        None => (target.to_string(), Vec::new()),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+    };
+
+    if request
+        .header("Transfer-Encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"))
+    {
+        request.body = read_chunked(reader)?;
+    } else if let Some(length) = request
+        .header("Content-Length")
+        .and_then(|value| value.parse::<usize>().ok())
+    {
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        request.body = body;
+    }
+
+    Ok(Some(request))
+}
+
+pub fn write_response<W: Write>(writer: &mut W, response: &Response) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    )?;
+    for (name, value) in &response.headers {
+        write!(writer, "{}: {}\r\n", name, value)?;
+    }
+    writer.write_all(b"\r\n")?;
+    writer.write_all(&response.body)?;
+    writer.flush()
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        reader.read_line(&mut size_line)?;
+        let size = usize::from_str_radix(size_line.trim().split(';').next().unwrap_or("0"), 16)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut chunk = vec![0; size + 2];
+        reader.read_exact(&mut chunk)?;
+        if size == 0 {
+            return Ok(body);
+        }
+        body.extend_from_slice(&chunk[..size]);
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                // The digits are decoded from the bytes, as slicing the string could split a
+                // multi-byte character.
+                let byte = std::str::from_utf8(&bytes[index + 1..index + 3])
+                    .ok()
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn disposition_value(disposition: &str, key: &str) -> Option<String> {
+    disposition.split(';').find_map(|param| {
+        let (name, value) = param.trim().split_once('=')?;
+        (name == key).then(|| value.trim_matches('"').to_string())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Split `data` on `separator`, dropping the content before the first separator.
+fn split<'a>(data: &'a [u8], separator: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut rest = data;
+
+    while let Some(start) = find(rest, separator) {
+        rest = &rest[start + separator.len()..];
+        let end = find(rest, separator).unwrap_or(rest.len());
+        parts.push(&rest[..end]);
+    }
+
+    parts
+}
+
+const fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_is_percent_decoded() {
+        assert_eq!(
+            parse_query("filename=base%2Dfiles%25&name=a+b"),
+            vec![
+                ("filename".to_string(), "base-files%".to_string()),
+                ("name".to_string(), "a b".to_string())
+            ]
+        );
+        assert_eq!(percent_decode("%C3%A4"), "ä");
+        assert_eq!(percent_decode("%ä%"), "%ä%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
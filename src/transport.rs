@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Sending requests to the API.
+//!
+//! Every request made by [`Fossology`](crate::Fossology) goes through a [`Transport`]. The default
+//! [`HttpTransport`] sends them with a `reqwest` client. Implement the trait to use another HTTP
+//! stack, to wrap the requests with logging, metrics or signing, or to respond to them in tests.
+//!
+//! The trait uses the blocking [`Request`] and [`Response`] of `reqwest`, so implementations have
+//! to depend on the same major version of `reqwest` as this crate. Another HTTP stack has to
+//! convert the request from and the response to these types. Every request carries its timeout,
+//! ten minutes unless overridden, which `reqwest` clients apply when executing the request.
+
+use std::{fmt::Debug, sync::Arc};
+
+use reqwest::blocking::{Client, Request, Response};
+
+use crate::FossologyError;
+
+/// Sends requests to the API.
+pub trait Transport: Debug + Send + Sync {
+    /// Sends the request and returns the response of the API.
+    ///
+    /// # Errors
+    ///
+    /// - Request can't be sent or the response can't be received.
+    fn execute(&self, request: Request) -> Result<Response, FossologyError>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+        (**self).execute(request)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+        (**self).execute(request)
+    }
+}
+
+/// Sends the requests with a `reqwest` client.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Creates a transport with a default client.
+    ///
+    /// # Errors
+    ///
+    /// - Client can't be initialized.
+    pub fn new() -> Result<Self, FossologyError> {
+        Ok(Self {
+            client: Client::builder().build()?,
+        })
+    }
+}
+
+impl From<Client> for HttpTransport {
+    fn from(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+        Ok(self.client.execute(request)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use crate::{job::get_jobs, testing::MockFossology, upload::new_upload_from_file, Fossology};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct CountingTransport {
+        inner: HttpTransport,
+        requests: AtomicUsize,
+        paths: Mutex<Vec<String>>,
+        timeouts: Mutex<Vec<Option<Duration>>>,
+    }
+
+    impl Transport for CountingTransport {
+        fn execute(&self, request: Request) -> Result<Response, FossologyError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            self.paths
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_string());
+            self.timeouts
+                .lock()
+                .unwrap()
+                .push(request.timeout().copied());
+
+            self.inner.execute(request)
+        }
+    }
+
+    #[test]
+    fn requests_go_through_custom_transport() {
+        let mock = MockFossology::start();
+        let transport = Arc::new(CountingTransport {
+            inner: HttpTransport::new().unwrap(),
+            requests: AtomicUsize::new(0),
+            paths: Mutex::new(Vec::new()),
+            timeouts: Mutex::new(Vec::new()),
+        });
+
+        let fossology =
+            Fossology::with_transport(&mock.uri(), mock.token(), Arc::clone(&transport)).unwrap();
+        let requests_for_version = transport.requests.load(Ordering::SeqCst);
+        assert!(requests_for_version > 0);
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap();
+
+        assert_eq!(
+            transport.requests.load(Ordering::SeqCst),
+            requests_for_version + 2
+        );
+        let paths = transport.paths.lock().unwrap().clone();
+        assert!(paths[paths.len() - 2].ends_with("/uploads"));
+        assert!(paths[paths.len() - 1].ends_with("/jobs"));
+        assert!(transport
+            .timeouts
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|timeout| *timeout == Some(Duration::from_secs(600))));
+    }
+}
@@ -4,12 +4,35 @@
 
 //! Manage uploads.
 //!
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    fs::{self, File},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
 
-use reqwest::blocking::multipart::Form;
+use chrono::{DateTime, Utc};
+use log::warn;
+use md5::Md5;
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
 
-use crate::{Fossology, FossologyError, FossologyResponse, InfoWithNumber};
+use crate::{
+    copy_response_to, total_pages, utilities::deserialize_date_time, Fossology, FossologyError,
+    FossologyResponse, InfoWithNumber, Page, Paginator,
+};
+
+pub mod permissions;
+pub mod tree;
 
 /// # Errors
 ///
@@ -22,25 +45,642 @@ pub fn new_upload_from_file<P: AsRef<Path>>(
     folder_id: i32,
     path_to_file: P,
 ) -> Result<NewUpload, FossologyError> {
-    let form = Form::new().file("fileInput", &path_to_file)?;
+    let path = path_to_file.as_ref();
+    let file = File::open(path)?;
+    let length = file.metadata()?.len();
+
+    new_upload_from_content(fossology, folder_id, &file_name(path), file, length, None)
+}
+
+/// Upload the content as a file with the name.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_bytes(
+    fossology: &Fossology,
+    folder_id: i32,
+    file_name: &str,
+    content: Vec<u8>,
+) -> Result<NewUpload, FossologyError> {
+    let length = content.len() as u64;
+
+    new_upload_from_content(
+        fossology,
+        folder_id,
+        file_name,
+        Cursor::new(content),
+        length,
+        None,
+    )
+}
+
+/// Upload the content read from `reader` as a file with the name. The content is streamed, so
+/// it doesn't need to fit in memory, but its length has to be known beforehand.
+///
+/// # Errors
+///
+/// - Reading the content fails.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_reader<R: Read + Send + 'static>(
+    fossology: &Fossology,
+    folder_id: i32,
+    file_name: &str,
+    reader: R,
+    length: u64,
+) -> Result<NewUpload, FossologyError> {
+    new_upload_from_content(fossology, folder_id, file_name, reader, length, None)
+}
+
+/// Upload a large file, reporting the progress and retrying if the connection fails.
+///
+/// The file is streamed from the disk and the request uses [`UploadOptions::timeout`] instead
+/// of the default timeout. The API doesn't support chunked or resumable uploads, so a failed
+/// upload is sent again from the start.
+///
+/// The server may have received the file before the connection dropped. To not create a
+/// duplicate, the uploads of the file in the folder are looked up with [`filesearch`] before the
+/// first attempt and again before every retry, and an upload that didn't exist before the first
+/// attempt is returned as the upload created by this call. If a lookup fails, the upload is sent
+/// again. The retries wait [`UploadOptions::retry_delay`], doubled after every retry.
+///
+/// # Errors
+///
+/// - File can't be opened or read.
+/// - Error while sending request and no retries are left.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_file_with_options<P: AsRef<Path>>(
+    fossology: &Fossology,
+    folder_id: i32,
+    path_to_file: P,
+    options: &UploadOptions,
+) -> Result<NewUpload, FossologyError> {
+    let path = path_to_file.as_ref();
+    let file_name = file_name(path);
+
+    // Hash of the file and its uploads in the folder before the first attempt, `None` if the
+    // uploads are not known.
+    let uploaded_before = if options.retries > 0 {
+        let hash = Hash::from_file(path)?;
+        match upload_ids_in_folder(fossology, &hash, folder_id) {
+            Ok(upload_ids) => Some((hash, upload_ids)),
+            Err(err) => {
+                warn!("Uploads of {} can't be looked up: {}", path.display(), err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut retries_left = options.retries;
+    let mut retry_delay = options.retry_delay;
+    loop {
+        let file = File::open(path)?;
+        let total = file.metadata()?.len();
+        let reader = ProgressReader {
+            inner: file,
+            sent: 0,
+            total,
+            progress: options.progress.clone(),
+        };
+        match new_upload_from_content(
+            fossology,
+            folder_id,
+            &file_name,
+            reader,
+            total,
+            options.timeout,
+        ) {
+            Err(FossologyError::RequestError(err)) if retries_left > 0 => {
+                warn!("Upload of {} failed, retrying: {}", path.display(), err);
+                retries_left -= 1;
+                thread::sleep(retry_delay);
+                retry_delay *= 2;
+
+                let Some((hash, upload_ids_before)) = &uploaded_before else {
+                    continue;
+                };
+                match upload_ids_in_folder(fossology, hash, folder_id) {
+                    Ok(upload_ids) => {
+                        let received = upload_ids
+                            .into_iter()
+                            .filter(|id| !upload_ids_before.contains(id))
+                            .max();
+                        if let Some(upload_id) = received {
+                            return Ok(NewUpload {
+                                upload_id,
+                                existing: false,
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Uploads of {} can't be looked up: {}", path.display(), err);
+                    }
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Ids of the uploads of the file with the hash in the folder.
+fn upload_ids_in_folder(
+    fossology: &Fossology,
+    hash: &Hash,
+    folder_id: i32,
+) -> Result<Vec<i32>, FossologyError> {
+    let (archives, _) = find_uploads_by_hash(fossology, hash, None)?;
+
+    Ok(archives
+        .into_iter()
+        .filter(|upload| upload.folder_id == folder_id)
+        .map(|upload| upload.id)
+        .collect())
+}
+
+/// Name of the uploaded file at the path.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Upload the `length` bytes read from `reader` as a file with the name.
+fn new_upload_from_content<R: Read + Send + 'static>(
+    fossology: &Fossology,
+    folder_id: i32,
+    file_name: &str,
+    reader: R,
+    length: u64,
+    timeout: Option<Duration>,
+) -> Result<NewUpload, FossologyError> {
+    let mut builder = fossology
+        .init_post_with_token("uploads")
+        .param("folderId", folder_id.to_string())
+        .file("fileInput", file_name, reader, length);
+
+    builder = if let Some(timeout) = timeout {
+        builder.timeout(timeout)
+    } else {
+        builder
+    };
+
+    let response = builder
+        .send()?
+        .json::<FossologyResponse<InfoWithNumber>>()?;
+
+    match response {
+        FossologyResponse::Response(res) => Ok(NewUpload {
+            upload_id: res.message,
+            existing: false,
+        }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Called with the progress of an upload as the file is read.
+pub type ProgressCallback = Arc<dyn Fn(UploadProgress) + Send + Sync>;
+
+/// Options for [`new_upload_from_file_with_options`].
+#[derive(Clone)]
+pub struct UploadOptions {
+    /// Called whenever more of the file has been read for sending.
+    pub progress: Option<ProgressCallback>,
+
+    /// Timeout of the whole upload. Uses the default timeout of ten minutes if `None`.
+    pub timeout: Option<Duration>,
+
+    /// Number of times the upload is retried if sending it fails.
+    pub retries: u32,
+
+    /// Time to wait before the first retry. Doubled after every retry.
+    pub retry_delay: Duration,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            progress: None,
+            timeout: None,
+            retries: 0,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl fmt::Debug for UploadOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UploadOptions")
+            .field("progress", &self.progress.as_ref().map(|_| "callback"))
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .field("retry_delay", &self.retry_delay)
+            .finish()
+    }
+}
+
+/// Progress of an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// Bytes of the file read for sending.
+    pub sent: u64,
+
+    /// Size of the file.
+    pub total: u64,
+}
+
+/// Reports the progress as the file is read by the request.
+struct ProgressReader<R> {
+    inner: R,
+    sent: u64,
+    total: u64,
+    progress: Option<ProgressCallback>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        if read > 0 {
+            self.sent += read as u64;
+            if let Some(progress) = &self.progress {
+                progress(UploadProgress {
+                    sent: self.sent,
+                    total: self.total,
+                });
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+/// Upload a file unless an upload of the same file already exists.
+///
+/// Existing uploads are found by the SHA256 of the file with [`find_upload_by_sha256`]. If one
+/// exists, its id is returned with [`NewUpload::existing`] set instead of creating a duplicate.
+///
+/// # Errors
+///
+/// - File can't be read.
+/// - Existing uploads can't be searched, see [`find_upload_by_sha256`].
+/// - File can't be uploaded, see [`new_upload_from_file`].
+pub fn new_upload_from_file_deduplicated<P: AsRef<Path>>(
+    fossology: &Fossology,
+    folder_id: i32,
+    path_to_file: P,
+    deduplicate: Deduplicate,
+) -> Result<NewUpload, FossologyError> {
+    let hash = Hash::from_file(&path_to_file)?;
+
+    match find_duplicate(fossology, folder_id, &hash, deduplicate)? {
+        Some(existing) => Ok(existing),
+        None => new_upload_from_file(fossology, folder_id, path_to_file),
+    }
+}
+
+/// Upload the content as a file with the name unless an upload of the same content already
+/// exists, like [`new_upload_from_file_deduplicated`].
+///
+/// # Errors
+///
+/// - Existing uploads can't be searched, see [`find_upload_by_sha256`].
+/// - Content can't be uploaded, see [`new_upload_from_bytes`].
+pub fn new_upload_from_bytes_deduplicated(
+    fossology: &Fossology,
+    folder_id: i32,
+    file_name: &str,
+    content: Vec<u8>,
+    deduplicate: Deduplicate,
+) -> Result<NewUpload, FossologyError> {
+    let hash = Hash::from_reader(content.as_slice())?;
+
+    match find_duplicate(fossology, folder_id, &hash, deduplicate)? {
+        Some(existing) => Ok(existing),
+        None => new_upload_from_bytes(fossology, folder_id, file_name, content),
+    }
+}
+
+/// Upload the content read from `reader` as a file with the name unless an upload of the same
+/// content already exists, like [`new_upload_from_file_deduplicated`].
+///
+/// The content is read twice: once to calculate its hash, after which `reader` is seeked back
+/// to where it was, and once to upload it.
+///
+/// # Errors
+///
+/// - Reading or seeking the content fails.
+/// - Existing uploads can't be searched, see [`find_upload_by_sha256`].
+/// - Content can't be uploaded, see [`new_upload_from_reader`].
+pub fn new_upload_from_reader_deduplicated<R: Read + Seek + Send + 'static>(
+    fossology: &Fossology,
+    folder_id: i32,
+    file_name: &str,
+    mut reader: R,
+    length: u64,
+    deduplicate: Deduplicate,
+) -> Result<NewUpload, FossologyError> {
+    let start = reader.stream_position()?;
+    let hash = Hash::from_reader((&mut reader).take(length))?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    match find_duplicate(fossology, folder_id, &hash, deduplicate)? {
+        Some(existing) => Ok(existing),
+        None => new_upload_from_reader(fossology, folder_id, file_name, reader, length),
+    }
+}
 
+/// Existing upload of the content with the hash, if there is one where `deduplicate` looks.
+fn find_duplicate(
+    fossology: &Fossology,
+    folder_id: i32,
+    hash: &Hash,
+    deduplicate: Deduplicate,
+) -> Result<Option<NewUpload>, FossologyError> {
+    let Some(sha256) = &hash.sha256 else {
+        return Ok(None);
+    };
+    let folder_id = match deduplicate {
+        Deduplicate::Anywhere => None,
+        Deduplicate::InFolder => Some(folder_id),
+    };
+
+    let upload = find_upload_by_sha256(fossology, sha256, folder_id, None)?;
+
+    Ok(upload.map(|upload| NewUpload {
+        upload_id: upload.id,
+        existing: true,
+    }))
+}
+
+/// Where [`new_upload_from_file_deduplicated`] and the other deduplicated uploads look for an
+/// existing upload of the content.
+///
+/// Uploads from a URL, version control or the server can't be deduplicated, because the
+/// content is only fetched by Fossology and its hash is not known beforehand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deduplicate {
+    /// Any upload the user can access.
+    Anywhere,
+
+    /// Only uploads in the folder the file would be uploaded to.
+    InFolder,
+}
+
+/// Find the latest upload of the file with the SHA256, optionally only in the folder. Uploads
+/// that only contain the file are not returned.
+///
+/// # Errors
+///
+/// - Files can't be searched, see [`filesearch`].
+/// - Uploads can't be retrieved, see [`get_upload_by_id`].
+pub fn find_upload_by_sha256(
+    fossology: &Fossology,
+    sha256: &str,
+    folder_id: Option<i32>,
+    group_name: Option<&str>,
+) -> Result<Option<Upload>, FossologyError> {
+    let (archives, _) = find_uploads_by_hash(fossology, &Hash::from_sha256(sha256), group_name)?;
+
+    Ok(latest(
+        archives
+            .into_iter()
+            .filter(|upload| folder_id.is_none_or(|id| upload.folder_id == id))
+            .collect(),
+    ))
+}
+
+/// Find the uploads in which the file with the hash was found with [`filesearch`]. Returns the
+/// uploads of the file itself and the uploads that contain it separately.
+pub(crate) fn find_uploads_by_hash(
+    fossology: &Fossology,
+    hash: &Hash,
+    group_name: Option<&str>,
+) -> Result<(Vec<Upload>, Vec<Upload>), FossologyError> {
+    let mut upload_ids = filesearch(
+        fossology,
+        std::slice::from_ref(hash),
+        group_name.map(str::to_string),
+    )?
+    .into_iter()
+    .flat_map(|response| response.uploads)
+    .collect::<Vec<_>>();
+    upload_ids.sort_unstable();
+    upload_ids.dedup();
+
+    let mut uploads = Vec::new();
+    for upload_id in upload_ids {
+        if let Some(upload) = get_upload_by_id(fossology, upload_id)? {
+            uploads.push(upload);
+        }
+    }
+
+    Ok(uploads
+        .into_iter()
+        .partition(|upload| is_same_archive(hash, &upload.hash)))
+}
+
+/// Newest of the uploads. Uploads with the same date are ordered by their id.
+pub(crate) fn latest(uploads: Vec<Upload>) -> Option<Upload> {
+    uploads
+        .into_iter()
+        .max_by_key(|upload| (upload.upload_date, upload.id))
+}
+
+/// Compares the strongest hash available in both.
+fn is_same_archive(search: &Hash, archive: &Hash) -> bool {
+    let same = |search: &Option<String>, archive: &Option<String>| match (search, archive) {
+        (Some(search), Some(archive)) => Some(search.eq_ignore_ascii_case(archive)),
+        _ => None,
+    };
+
+    same(&search.sha256, &archive.sha256)
+        .or_else(|| same(&search.sha1, &archive.sha1))
+        .or_else(|| same(&search.md5, &archive.md5))
+        .unwrap_or(false)
+}
+
+/// Upload a file that Fossology downloads from the URL.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_url(
+    fossology: &Fossology,
+    folder_id: i32,
+    url_upload: &UrlUpload,
+) -> Result<NewUpload, FossologyError> {
+    new_upload_from_json(fossology, folder_id, "url", url_upload)
+}
+
+/// Upload the contents of a version control repository.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_vcs(
+    fossology: &Fossology,
+    folder_id: i32,
+    vcs_upload: &VcsUpload,
+) -> Result<NewUpload, FossologyError> {
+    new_upload_from_json(fossology, folder_id, "vcs", vcs_upload)
+}
+
+/// Upload files from a path on the Fossology server, like a mounted directory.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn new_upload_from_server(
+    fossology: &Fossology,
+    folder_id: i32,
+    server_upload: &ServerUpload,
+) -> Result<NewUpload, FossologyError> {
+    new_upload_from_json(fossology, folder_id, "server", server_upload)
+}
+
+/// Create an upload whose source is described in the request body.
+fn new_upload_from_json<T: Serialize>(
+    fossology: &Fossology,
+    folder_id: i32,
+    upload_type: &str,
+    body: &T,
+) -> Result<NewUpload, FossologyError> {
     let response = fossology
-        .client
-        .post(&format!("{}/uploads", fossology.uri))
-        .bearer_auth(&fossology.token)
-        .header("folderId", folder_id.to_string())
-        .multipart(form)
+        .init_post_with_token("uploads")
+        .param("folderId", folder_id.to_string())
+        .param("uploadType", upload_type)
+        .json(body)
         .send()?
         .json::<FossologyResponse<InfoWithNumber>>()?;
 
     match response {
         FossologyResponse::Response(res) => Ok(NewUpload {
             upload_id: res.message,
+            existing: false,
         }),
         FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
     }
 }
 
+/// Source of an upload from URL.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlUpload {
+    pub url: String,
+
+    /// Name of the upload.
+    pub name: String,
+
+    /// Comma-separated list of file name suffixes or patterns to accept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept: Option<String>,
+
+    /// Comma-separated list of file name suffixes or patterns to reject.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_recursion_depth: Option<i32>,
+}
+
+impl UrlUpload {
+    pub fn new(url: &str, name: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            name: name.to_string(),
+            accept: None,
+            reject: None,
+            max_recursion_depth: None,
+        }
+    }
+}
+
+/// Source of an upload from the file system of the Fossology server.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpload {
+    /// Path of a file or directory on the server. The last part of the path can contain
+    /// wildcards to only upload the matching files.
+    pub path: String,
+
+    /// Name of the upload.
+    pub name: String,
+}
+
+impl ServerUpload {
+    /// Upload the file or directory at the path on the server with the name. A directory is
+    /// uploaded with all of its files.
+    pub fn new(path: &str, name: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// Upload the files in the directory whose names match the wildcard, like `*.tar.gz`.
+    pub fn with_wildcard(directory: &str, wildcard: &str, name: &str) -> Self {
+        Self {
+            path: format!("{}/{}", directory.trim_end_matches('/'), wildcard),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Source of an upload from version control.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VcsUpload {
+    pub vcs_type: VcsType,
+
+    pub vcs_url: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcs_branch: Option<String>,
+
+    /// Name of the upload.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcs_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcs_username: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcs_password: Option<String>,
+}
+
+impl VcsUpload {
+    pub fn new(vcs_type: VcsType, vcs_url: &str) -> Self {
+        Self {
+            vcs_type,
+            vcs_url: vcs_url.to_string(),
+            vcs_branch: None,
+            vcs_name: None,
+            vcs_username: None,
+            vcs_password: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VcsType {
+    Git,
+    Svn,
+}
+
 /// # Errors
 ///
 /// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
@@ -51,9 +691,7 @@ pub fn get_upload_by_id(
     upload_id: i32,
 ) -> Result<Option<Upload>, FossologyError> {
     let response = fossology
-        .client
-        .get(&format!("{}/uploads/{}", fossology.uri, upload_id))
-        .bearer_auth(&fossology.token)
+        .init_get_with_token(&format!("uploads/{}", upload_id))
         .send()?
         .json::<FossologyResponse<Upload>>()?;
 
@@ -69,27 +707,174 @@ pub fn get_upload_by_id(
     }
 }
 
+/// Get the id of the top level item in the upload's file tree.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`InfoWithNumber`] or [`Info`](crate::Info).
+/// - Response is not [`InfoWithNumber`].
+pub fn get_top_item(fossology: &Fossology, upload_id: i32) -> Result<i32, FossologyError> {
+    let response = fossology
+        .init_get_with_token(&format!("uploads/{}/topitem", upload_id))
+        .send()?
+        .json::<FossologyResponse<InfoWithNumber>>()?;
+
+    match response {
+        FossologyResponse::Response(res) => Ok(res.message),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Download the originally uploaded file into `writer`.
+///
+/// The content is streamed, so the file is not buffered in memory. Returns the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response is not successful.
+/// - Writing to `writer` fails.
+pub fn download_upload<W: Write + ?Sized>(
+    fossology: &Fossology,
+    upload_id: i32,
+    writer: &mut W,
+) -> Result<u64, FossologyError> {
+    let response = fossology
+        .init_get_with_token(&format!("uploads/{}/download", upload_id))
+        .send()?;
+
+    copy_response_to(response, writer)
+}
+
+/// Get one page of the uploads matching the filter. The page can contain fewer uploads than
+/// `limit`, see [`Page`].
+///
+/// # Errors
+///
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Vec`] of [`Upload`]s or [`Info`](crate::Info).
+/// - Response is not [`Vec`] of [`Upload`]s.
+pub fn list_uploads(
+    fossology: &Fossology,
+    filter: &UploadFilter,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<UploadPage, FossologyError> {
+    let mut page = get_uploads_page(fossology, filter, limit, page)?;
+    page.items.retain(|upload| filter.matches(upload));
+
+    Ok(page)
+}
+
+/// Iterate over all uploads matching the filter, retrieving the uploads page by page.
+pub fn iter_uploads<'a>(fossology: &'a Fossology, filter: &'a UploadFilter) -> UploadIter<'a> {
+    Paginator::new(move |limit, page| get_uploads_page(fossology, filter, Some(limit), Some(page)))
+        .retain(move |upload| filter.matches(upload))
+}
+
+/// Page of uploads before filtering the received uploads.
+fn get_uploads_page(
+    fossology: &Fossology,
+    filter: &UploadFilter,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<UploadPage, FossologyError> {
+    let mut builder = fossology.init_get_with_token("uploads");
+
+    builder = if let Some(folder_id) = filter.folder_id {
+        builder.query("folderId", folder_id)
+    } else {
+        builder
+    };
+
+    builder = if let Some(name) = &filter.name {
+        builder.query("name", name)
+    } else {
+        builder
+    };
+
+    let params = [
+        ("groupName", filter.group_name.clone()),
+        ("limit", limit.map(|limit| limit.to_string())),
+        ("page", page.map(|page| page.to_string())),
+    ];
+
+    for (name, value) in params {
+        if let Some(value) = value {
+            builder = builder.param(name, value);
+        }
+    }
+
+    let response = builder.send()?;
+
+    let total_pages = total_pages(&response);
+
+    match response.json::<FossologyResponse<Vec<Upload>>>()? {
+        FossologyResponse::Response(items) => Ok(UploadPage { items, total_pages }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Filters for [`list_uploads`]. Filters that are `None` are not used.
+#[derive(Debug, Default, Clone)]
+pub struct UploadFilter {
+    /// Only uploads in the folder or its subfolders.
+    pub folder_id: Option<i32>,
+
+    /// Only uploads whose name contains the text, ignoring case.
+    pub name: Option<String>,
+
+    pub group_name: Option<String>,
+}
+
+impl UploadFilter {
+    /// Returns true if the upload matches all of the filters.
+    ///
+    /// The folder is not checked, because the API also returns the uploads of subfolders.
+    pub fn matches(&self, upload: &Upload) -> bool {
+        self.name.as_ref().is_none_or(|name| {
+            upload
+                .upload_name
+                .to_lowercase()
+                .contains(&name.to_lowercase())
+        })
+    }
+}
+
+/// Page of uploads.
+pub type UploadPage = Page<Upload>;
+
+/// Iterator over all uploads matching a filter. Created with [`iter_uploads`].
+pub type UploadIter<'a> = Paginator<'a, Upload>;
+
+#[derive(Debug, Serialize)]
 pub struct NewUpload {
     pub upload_id: i32,
+
+    /// True if an existing upload of the same file was returned instead of creating a new one.
+    pub existing: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Upload {
-    #[serde(rename = "folderid")]
+    #[serde(rename = "folderid", alias = "folderId")]
     pub folder_id: i32,
 
-    #[serde(rename = "foldername")]
+    #[serde(rename = "foldername", alias = "folderName")]
     pub folder_name: String,
 
     pub id: i32,
 
     pub description: String,
 
-    #[serde(rename = "uploadname")]
+    #[serde(rename = "uploadname", alias = "uploadName")]
     pub upload_name: String,
 
-    #[serde(rename = "uploaddate")]
-    pub upload_date: String,
+    #[serde(rename = "uploaddate", alias = "uploadDate")]
+    #[serde(deserialize_with = "deserialize_date_time")]
+    pub upload_date: DateTime<Utc>,
 
     #[serde(default)]
     pub assignee: Option<i32>,
@@ -110,7 +895,7 @@ pub fn filesearch(
     let mut builder = fossology.init_post_with_token("filesearch").json(hashes);
 
     builder = if let Some(group_name) = group_name {
-        builder.header("groupName", group_name)
+        builder.param("groupName", group_name)
     } else {
         builder
     };
@@ -130,7 +915,114 @@ pub fn filesearch(
     }
 }
 
-#[derive(Deserialize, Debug)]
+/// Search for a large number of hashes by splitting them into chunks that are searched
+/// concurrently.
+///
+/// The results are keyed by the searched hashes, so a result can be looked up with the same
+/// [`Hash`](struct@Hash) that was searched for. Hashes that are not found are not included. If
+/// the API returns multiple results for the same hash, their uploads are merged.
+///
+/// # Errors
+///
+/// - Any of the chunks fails with the errors of [`filesearch`]. The remaining chunks are not
+///   searched after the first error.
+pub fn filesearch_batched(
+    fossology: &Fossology,
+    hashes: &[Hash],
+    group_name: Option<&str>,
+    batch: &FilesearchBatch,
+) -> Result<HashMap<Hash, FilesearchResponse>, FossologyError> {
+    let chunks = hashes.chunks(batch.chunk_size.max(1)).collect::<Vec<_>>();
+    let workers = batch.workers.clamp(1, chunks.len().max(1));
+
+    let next_chunk = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new(HashMap::new());
+    let first_error = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !failed.load(Ordering::SeqCst) {
+                    let index = next_chunk.fetch_add(1, Ordering::SeqCst);
+                    let Some(chunk) = chunks.get(index) else {
+                        break;
+                    };
+
+                    match filesearch(fossology, chunk, group_name.map(str::to_string)) {
+                        Ok(responses) => {
+                            let mut results =
+                                results.lock().unwrap_or_else(PoisonError::into_inner);
+                            for response in responses {
+                                for hash in chunk.iter().filter(|hash| hash.matches(&response.hash))
+                                {
+                                    merge_filesearch_response(
+                                        &mut results,
+                                        hash.clone(),
+                                        response.clone(),
+                                    );
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            failed.store(true, Ordering::SeqCst);
+                            first_error
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .get_or_insert(err);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(err) = first_error
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+    {
+        return Err(err);
+    }
+
+    Ok(results.into_inner().unwrap_or_else(PoisonError::into_inner))
+}
+
+fn merge_filesearch_response(
+    results: &mut HashMap<Hash, FilesearchResponse>,
+    searched: Hash,
+    response: FilesearchResponse,
+) {
+    if let Some(existing) = results.get_mut(&searched) {
+        for upload in response.uploads {
+            if !existing.uploads.contains(&upload) {
+                existing.uploads.push(upload);
+            }
+        }
+    } else {
+        results.insert(searched, response);
+    }
+}
+
+/// Options for [`filesearch_batched`].
+#[derive(Debug, Clone, Copy)]
+pub struct FilesearchBatch {
+    /// Number of hashes sent in one request.
+    pub chunk_size: usize,
+
+    /// Maximum number of requests running at the same time.
+    pub workers: usize,
+}
+
+impl Default for FilesearchBatch {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1000,
+            workers: 4,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FilesearchResponse {
     pub hash: Hash,
     pub findings: Option<Findings>,
@@ -139,14 +1031,14 @@ pub struct FilesearchResponse {
     pub message: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Findings {
     pub scanner: Vec<String>,
     pub conclusion: Vec<String>,
     pub copyright: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Eq, PartialEq, Hash)]
 pub struct Hash {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub sha1: Option<String>,
@@ -154,6 +1046,8 @@ pub struct Hash {
     pub md5: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub sha256: Option<String>,
+    /// Size of the file in bytes. Hashes calculated with [`Hash::from_file`] or
+    /// [`Hash::from_reader`] leave the size empty for files larger than [`i32::MAX`] bytes.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub size: Option<i32>,
 }
@@ -179,34 +1073,177 @@ impl Hash {
             ..Self::default()
         }
     }
+
+    /// Returns true if `other` describes the same file. The checksums known by both are compared
+    /// ignoring case, and at least one of them has to be known by both.
+    pub fn matches(&self, other: &Self) -> bool {
+        let checksums = [
+            (&self.sha1, &other.sha1),
+            (&self.md5, &other.md5),
+            (&self.sha256, &other.sha256),
+        ];
+        let mut compared = checksums
+            .iter()
+            .filter_map(|(own, other)| Some((own.as_ref()?, other.as_ref()?)))
+            .peekable();
+
+        compared.peek().is_some()
+            && compared.all(|(own, other)| own.eq_ignore_ascii_case(other))
+            && self
+                .size
+                .zip(other.size)
+                .is_none_or(|(own, other)| own == other)
+    }
+
+    /// Calculate SHA1, MD5, SHA256 and size of the file in one pass.
+    ///
+    /// # Errors
+    ///
+    /// - File can't be opened or read.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FossologyError> {
+        let file = File::open(path)?;
+
+        Self::from_reader(file)
+    }
+
+    /// Calculate SHA1, MD5, SHA256 and size of the content in one pass.
+    ///
+    /// The hashes are uppercase hex strings. Size is left empty if it doesn't fit in [`i32`].
+    ///
+    /// # Errors
+    ///
+    /// - Reading from `reader` fails.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, FossologyError> {
+        let mut sha1 = Sha1::new();
+        let mut md5 = Md5::new();
+        let mut sha256 = Sha256::new();
+        let mut size: u64 = 0;
+        let mut buffer = vec![0; 64 * 1024];
+
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            sha1.update(&buffer[..read]);
+            md5.update(&buffer[..read]);
+            sha256.update(&buffer[..read]);
+            size += read as u64;
+        }
+
+        Ok(Self {
+            sha1: Some(hex::encode_upper(sha1.finalize())),
+            md5: Some(hex::encode_upper(md5.finalize())),
+            sha256: Some(hex::encode_upper(sha256.finalize())),
+            size: i32::try_from(size).ok(),
+        })
+    }
+}
+
+/// Calculate the [`Hash`](struct@Hash) of every file in the directory and its subdirectories.
+///
+/// Symbolic links are not followed. The files are returned sorted by path.
+///
+/// # Errors
+///
+/// - Directory can't be read.
+/// - Any of the files can't be opened or read.
+pub fn hash_directory<P: AsRef<Path>>(path: P) -> Result<Vec<(PathBuf, Hash)>, FossologyError> {
+    let mut hashes = Vec::new();
+    let mut directories = vec![path.as_ref().to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                directories.push(entry.path());
+            } else if file_type.is_file() {
+                let hash = Hash::from_file(entry.path())?;
+                hashes.push((entry.path(), hash));
+            }
+        }
+    }
+
+    hashes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(hashes)
 }
 
 #[cfg(test)]
 mod test {
-    use std::{thread, time::Duration};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use rand::{distributions::Alphanumeric, Rng};
 
     use crate::{
-        auth::test::create_test_fossology_with_writetoken,
-        job::{get_jobs, JobStatus},
+        testing::MockFossology,
+        transport::{HttpTransport, Transport},
         utilities::hash256_for_path,
     };
 
     use super::*;
 
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
+
+    #[test]
+    fn hash_is_calculated_for_file() {
+        let hash = Hash::from_file(ARCHIVE).unwrap();
+
+        assert_eq!(
+            hash.sha1.unwrap(),
+            "D4D663FC2877084362FB2297337BE05684869B00"
+        );
+        assert_eq!(hash.md5.unwrap(), "8C49791FF61C865C69742B9A80AACB0E");
+        assert_eq!(
+            hash.sha256.unwrap(),
+            "CF610763B6FC4E7F6C066FD6BED1D580F6B0FD9E1F91C26A18900117A3D5622E"
+        );
+        assert_eq!(hash.size, Some(65368));
+    }
+
+    #[test]
+    fn hashes_are_calculated_for_directory() {
+        let name = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect::<String>();
+        let directory = std::env::temp_dir().join(format!("fossology-rs-{}", name));
+        fs::create_dir_all(directory.join("nested")).unwrap();
+        fs::copy(ARCHIVE, directory.join("nested/archive.tar.xz")).unwrap();
+        fs::write(directory.join("text.txt"), "text").unwrap();
+
+        let hashes = hash_directory(&directory);
+        fs::remove_dir_all(&directory).unwrap();
+        let hashes = hashes.unwrap();
+
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[0].0, directory.join("nested/archive.tar.xz"));
+        assert_eq!(hashes[0].1, Hash::from_file(ARCHIVE).unwrap());
+        assert_eq!(hashes[1].0, directory.join("text.txt"));
+        assert_eq!(hashes[1].1, Hash::from_reader(&b"text"[..]).unwrap());
+    }
+
     #[test]
     fn create_upload_from_file() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
-        new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
     }
 
     #[test]
     fn filesearch_for_archive() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
-        let sha256 = hash256_for_path("tests/data/base-files_11.tar.xz");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+        let sha256 = hash256_for_path(ARCHIVE);
 
-        let upload =
-            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
 
         let hashes = vec![Hash::from_sha256(&sha256)];
 
@@ -216,28 +1253,74 @@ mod test {
     }
 
     #[test]
-    fn upload_is_retrievable_by_id() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+    fn batched_filesearch_spans_chunks() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
-        let upload =
-            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        let archive = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let generated =
+            new_upload_from_bytes(&fossology, 1, "generated.txt", b"generated".to_vec()).unwrap();
+        let archive_hash = Hash::from_file(ARCHIVE).unwrap();
+        let generated_hash = Hash::from_reader(&b"generated"[..]).unwrap();
 
-        while get_jobs(&fossology, Some(upload.upload_id), None, None, None).unwrap()[0].status
-            == JobStatus::Processing
-        {
-            thread::sleep(Duration::from_secs(1));
-        }
+        // The API returns all checksums in upper case, the searched hashes are used as keys.
+        let by_sha256 = Hash::from_sha256(&archive_hash.sha256.unwrap().to_lowercase());
+        let by_sha1 = Hash::from_sha1(&generated_hash.sha1.unwrap().to_lowercase());
+        let by_md5 = Hash::from_md5(&archive_hash.md5.unwrap());
+        let hashes = vec![
+            by_sha256.clone(),
+            Hash::from_sha256("doesnotexist"),
+            by_sha1.clone(),
+            Hash::from_sha256("doesnotexisteither"),
+            by_md5.clone(),
+        ];
+        let batch = FilesearchBatch {
+            chunk_size: 2,
+            workers: 2,
+        };
+
+        let filesearch = filesearch_batched(&fossology, &hashes, Some("fossy"), &batch).unwrap();
+
+        assert_eq!(filesearch.len(), 3);
+        assert_eq!(filesearch[&by_sha256].uploads, vec![archive.upload_id]);
+        assert_eq!(filesearch[&by_sha1].uploads, vec![generated.upload_id]);
+        assert_eq!(filesearch[&by_md5].uploads, vec![archive.upload_id]);
+    }
+
+    #[test]
+    fn upload_is_retrievable_by_id() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
 
         let upload = get_upload_by_id(&fossology, upload.upload_id)
             .unwrap()
             .unwrap();
 
         assert_eq!(upload.folder_id, 1);
+        assert_eq!(upload.upload_name, "base-files_11.tar.xz");
+        assert_eq!(upload.hash, Hash::from_file(ARCHIVE).unwrap());
+    }
+
+    #[test]
+    fn download_uploaded_file() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let mut content = Vec::new();
+        let size = download_upload(&fossology, upload.upload_id, &mut content).unwrap();
+
+        assert_eq!(content, std::fs::read(ARCHIVE).unwrap());
+        assert_eq!(size, content.len() as u64);
     }
 
     #[test]
     fn non_existing_upload_id_returns_none() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
         let upload = get_upload_by_id(&fossology, 99999).unwrap();
 
@@ -246,11 +1329,227 @@ mod test {
 
     #[test]
     fn non_existing_hash_for_filesearch_works() {
-        let fossology = create_test_fossology_with_writetoken("http://localhost:8080/repo/api/v1");
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
 
         let hashes = vec![Hash::from_sha256("doesnotexist")];
         let filesearch = filesearch(&fossology, &hashes, None).unwrap();
 
         assert!(filesearch.is_empty());
     }
+
+    #[test]
+    fn uploads_are_created_from_bytes_and_readers() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+        let content = std::fs::read(ARCHIVE).unwrap();
+        let hash = Hash::from_file(ARCHIVE).unwrap();
+
+        let from_bytes =
+            new_upload_from_bytes(&fossology, 1, "generated.tar.xz", content.clone()).unwrap();
+        let length = content.len() as u64;
+        let from_reader = new_upload_from_reader(
+            &fossology,
+            1,
+            "streamed.tar.xz",
+            std::io::Cursor::new(content),
+            length,
+        )
+        .unwrap();
+
+        for (upload, name) in [
+            (from_bytes, "generated.tar.xz"),
+            (from_reader, "streamed.tar.xz"),
+        ] {
+            let upload = get_upload_by_id(&fossology, upload.upload_id)
+                .unwrap()
+                .unwrap();
+            assert_eq!(upload.upload_name, name);
+            assert_eq!(upload.hash, hash);
+        }
+    }
+
+    #[test]
+    fn upload_is_created_from_server_path() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let server_upload = ServerUpload::with_wildcard("/mnt/mirror/", "*.tar.gz", "mirror");
+        assert_eq!(server_upload.path, "/mnt/mirror/*.tar.gz");
+
+        let upload = new_upload_from_server(&fossology, 1, &server_upload).unwrap();
+        let upload = get_upload_by_id(&fossology, upload.upload_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(upload.upload_name, "mirror");
+    }
+
+    #[test]
+    fn duplicate_uploads_are_not_created() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload =
+            new_upload_from_file_deduplicated(&fossology, 1, ARCHIVE, Deduplicate::Anywhere)
+                .unwrap();
+        assert!(!upload.existing);
+
+        let duplicate =
+            new_upload_from_file_deduplicated(&fossology, 2, ARCHIVE, Deduplicate::Anywhere)
+                .unwrap();
+        assert!(duplicate.existing);
+        assert_eq!(duplicate.upload_id, upload.upload_id);
+
+        let in_folder =
+            new_upload_from_file_deduplicated(&fossology, 2, ARCHIVE, Deduplicate::InFolder)
+                .unwrap();
+        assert!(!in_folder.existing);
+        assert_eq!(
+            mock.upload_ids(),
+            vec![upload.upload_id, in_folder.upload_id]
+        );
+
+        let latest =
+            new_upload_from_file_deduplicated(&fossology, 2, ARCHIVE, Deduplicate::Anywhere)
+                .unwrap();
+        assert_eq!(latest.upload_id, in_folder.upload_id);
+    }
+
+    #[test]
+    fn duplicate_content_is_not_uploaded_from_bytes_or_readers() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+        let content = b"content".to_vec();
+
+        let upload = new_upload_from_bytes_deduplicated(
+            &fossology,
+            1,
+            "content.txt",
+            content.clone(),
+            Deduplicate::InFolder,
+        )
+        .unwrap();
+        assert!(!upload.existing);
+
+        let mut reader = std::io::Cursor::new(b"prefix content".to_vec());
+        reader.set_position(7);
+        let duplicate = new_upload_from_reader_deduplicated(
+            &fossology,
+            1,
+            "content.txt",
+            reader,
+            content.len() as u64,
+            Deduplicate::InFolder,
+        )
+        .unwrap();
+        assert!(duplicate.existing);
+        assert_eq!(duplicate.upload_id, upload.upload_id);
+
+        let mut reader = std::io::Cursor::new(b"prefix other".to_vec());
+        reader.set_position(7);
+        let other = new_upload_from_reader_deduplicated(
+            &fossology,
+            1,
+            "other.txt",
+            reader,
+            5,
+            Deduplicate::InFolder,
+        )
+        .unwrap();
+        assert!(!other.existing);
+        assert_eq!(
+            get_upload_by_id(&fossology, other.upload_id)
+                .unwrap()
+                .unwrap()
+                .hash,
+            Hash::from_reader(&b"other"[..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn upload_progress_is_reported() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+
+        let options = UploadOptions {
+            progress: Some({
+                let reported = Arc::clone(&reported);
+                Arc::new(move |progress: UploadProgress| reported.lock().unwrap().push(progress))
+            }),
+            ..UploadOptions::default()
+        };
+        let upload = new_upload_from_file_with_options(&fossology, 1, ARCHIVE, &options).unwrap();
+        assert!(!upload.existing);
+
+        let size = std::fs::metadata(ARCHIVE).unwrap().len();
+        let reported = reported.lock().unwrap().clone();
+        assert!(reported.windows(2).all(|w| w[0].sent < w[1].sent));
+        assert_eq!(
+            reported.last(),
+            Some(&UploadProgress {
+                sent: size,
+                total: size
+            })
+        );
+    }
+
+    /// Sends the first upload to the server but fails as if the connection dropped before the
+    /// response.
+    #[derive(Debug)]
+    struct DroppingTransport {
+        inner: HttpTransport,
+        dropped: AtomicBool,
+    }
+
+    impl Transport for DroppingTransport {
+        fn execute(
+            &self,
+            request: reqwest::blocking::Request,
+        ) -> Result<reqwest::blocking::Response, FossologyError> {
+            let is_upload =
+                request.method() == "POST" && request.url().path().ends_with("/uploads");
+            let response = self.inner.execute(request)?;
+
+            if is_upload && !self.dropped.swap(true, Ordering::SeqCst) {
+                let closed = reqwest::blocking::Request::new(
+                    reqwest::Method::GET,
+                    "http://127.0.0.1:1/".parse().unwrap(),
+                );
+                return self.inner.execute(closed);
+            }
+
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn failed_upload_is_retried_without_duplicates() {
+        let mock = MockFossology::start();
+        let earlier = new_upload_from_file(&mock.fossology(), 1, ARCHIVE).unwrap();
+        let transport = DroppingTransport {
+            inner: HttpTransport::new().unwrap(),
+            dropped: AtomicBool::new(false),
+        };
+        let fossology = Fossology::with_transport(&mock.uri(), mock.token(), transport).unwrap();
+
+        let options = UploadOptions {
+            retries: 1,
+            retry_delay: Duration::from_millis(10),
+            ..UploadOptions::default()
+        };
+        let upload = new_upload_from_file_with_options(&fossology, 1, ARCHIVE, &options).unwrap();
+        assert!(!upload.existing);
+        assert_eq!(mock.upload_ids(), vec![earlier.upload_id, upload.upload_id]);
+
+        let transport = DroppingTransport {
+            inner: HttpTransport::new().unwrap(),
+            dropped: AtomicBool::new(false),
+        };
+        let fossology = Fossology::with_transport(&mock.uri(), mock.token(), transport).unwrap();
+        assert!(matches!(
+            new_upload_from_file_with_options(&fossology, 1, ARCHIVE, &UploadOptions::default()),
+            Err(FossologyError::RequestError(_))
+        ));
+    }
 }
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Read and change who can access uploads.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{info_response, json_response, version::Capability, Fossology, FossologyError};
+
+/// Get the public permission of the upload and the permissions of the groups that have access
+/// to it.
+///
+/// # Errors
+///
+/// - Upload permissions are not supported by the API version.
+/// - Token doesn't have the permission to read the permissions of the upload.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Upload doesn't exist.
+/// - Response can't be serialized to [`UploadPermissions`] or [`Info`](crate::Info).
+pub fn get_upload_permissions(
+    fossology: &Fossology,
+    upload_id: i32,
+) -> Result<UploadPermissions, FossologyError> {
+    fossology.require(Capability::UploadPermissions)?;
+
+    let response = fossology
+        .init_get_with_token(&format!("uploads/{}/perm-groups", upload_id))
+        .send()?;
+
+    json_response(response)
+}
+
+/// Change the permissions of the upload, or of all uploads in the folder.
+///
+/// # Errors
+///
+/// - Upload permissions are not supported by the API version.
+/// - Token doesn't have the permission to change the permissions of the upload.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Upload, folder or group doesn't exist.
+pub fn set_upload_permissions(
+    fossology: &Fossology,
+    upload_id: i32,
+    change: &PermissionChange,
+) -> Result<(), FossologyError> {
+    fossology.require(Capability::UploadPermissions)?;
+
+    let response = fossology
+        .init_put_with_token(&format!("uploads/{}/permissions", upload_id))
+        .json(change)
+        .send()?;
+    info_response(response)?;
+
+    Ok(())
+}
+
+/// Level of access to an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// No access.
+    None,
+
+    /// View the upload and its findings.
+    ReadOnly,
+
+    /// Also make clearing decisions and schedule agents.
+    ReadWrite,
+
+    /// Also manage the clearing decisions of other users.
+    ClearingAdmin,
+
+    /// Full access, including changing the permissions and deleting the upload.
+    Admin,
+}
+
+/// Permissions of an upload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadPermissions {
+    /// Access of all users to the upload.
+    pub public_perm: Permission,
+
+    /// Groups that have access to the upload.
+    #[serde(default)]
+    pub perm_groups: Vec<GroupPermission>,
+}
+
+/// Access of a group to an upload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupPermission {
+    /// Access of the members of the group.
+    pub perm: Permission,
+
+    /// Id of the group.
+    #[serde(rename = "group_pk", alias = "groupPk")]
+    pub group_id: i32,
+
+    /// Name of the group.
+    #[serde(alias = "groupName")]
+    pub group_name: String,
+}
+
+/// Change to the permissions of uploads with [`set_upload_permissions`]. Permissions that are
+/// `None` are not changed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionChange {
+    /// Folder of the upload.
+    pub folder_id: i32,
+
+    /// Apply the change to all uploads in the folder instead of only the upload.
+    #[serde(rename = "allUploadsPermission")]
+    pub all_uploads_perm: bool,
+
+    /// Group whose access is changed to `new_perm`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<i32>,
+
+    /// Access of the group `group_id`. Only used together with `group_id`.
+    #[serde(rename = "newPermission", skip_serializing_if = "Option::is_none")]
+    pub new_perm: Option<Permission>,
+
+    /// Access of all users.
+    #[serde(rename = "publicPermission", skip_serializing_if = "Option::is_none")]
+    pub public_perm: Option<Permission>,
+}
+
+impl PermissionChange {
+    /// Change the access of all users to the upload in the folder.
+    pub const fn public(folder_id: i32, permission: Permission) -> Self {
+        Self {
+            folder_id,
+            all_uploads_perm: false,
+            group_id: None,
+            new_perm: None,
+            public_perm: Some(permission),
+        }
+    }
+
+    /// Change the access of the group to the upload in the folder.
+    pub const fn group(folder_id: i32, group_id: i32, permission: Permission) -> Self {
+        Self {
+            folder_id,
+            all_uploads_perm: false,
+            group_id: Some(group_id),
+            new_perm: Some(permission),
+            public_perm: None,
+        }
+    }
+
+    /// Apply the change to all uploads in the folder.
+    #[must_use]
+    pub const fn recursive(mut self) -> Self {
+        self.all_uploads_perm = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{testing::MockFossology, upload::new_upload_from_file, version::Capability};
+
+    use super::*;
+
+    const ARCHIVE: &str = "tests/data/base-files_11.tar.xz";
+
+    #[test]
+    fn upload_permissions_are_changed() {
+        let mock = MockFossology::with_version("1.6.0");
+        let fossology = mock.fossology();
+        let first = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+        let second = new_upload_from_file(&fossology, 1, ARCHIVE).unwrap();
+
+        let permissions = get_upload_permissions(&fossology, first.upload_id).unwrap();
+        assert_eq!(permissions.public_perm, Permission::None);
+        assert_eq!(permissions.perm_groups[0].group_name, "fossy");
+        assert_eq!(permissions.perm_groups[0].perm, Permission::Admin);
+
+        set_upload_permissions(
+            &fossology,
+            first.upload_id,
+            &PermissionChange::group(1, 5, Permission::ReadWrite),
+        )
+        .unwrap();
+        let permissions = get_upload_permissions(&fossology, first.upload_id).unwrap();
+        assert!(permissions
+            .perm_groups
+            .iter()
+            .any(|group| group.group_id == 5 && group.perm == Permission::ReadWrite));
+        assert_eq!(
+            get_upload_permissions(&fossology, second.upload_id)
+                .unwrap()
+                .perm_groups
+                .len(),
+            1
+        );
+
+        set_upload_permissions(
+            &fossology,
+            first.upload_id,
+            &PermissionChange::public(1, Permission::ReadOnly).recursive(),
+        )
+        .unwrap();
+        for upload_id in [first.upload_id, second.upload_id] {
+            assert_eq!(
+                get_upload_permissions(&fossology, upload_id)
+                    .unwrap()
+                    .public_perm,
+                Permission::ReadOnly
+            );
+        }
+
+        assert!(set_upload_permissions(
+            &fossology,
+            first.upload_id,
+            &PermissionChange::public(2, Permission::ReadOnly),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reading_upload_permissions_requires_valid_token() {
+        let mock = MockFossology::start();
+        let upload = new_upload_from_file(&mock.fossology(), 1, ARCHIVE).unwrap();
+        let fossology = Fossology::new(&mock.uri(), "invalid").unwrap();
+
+        assert!(matches!(
+            get_upload_permissions(&fossology, upload.upload_id),
+            Err(FossologyError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            get_upload_permissions(&mock.fossology(), upload.upload_id + 1),
+            Err(FossologyError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn upload_permissions_are_unsupported_before_1_5_1() {
+        let mock = MockFossology::with_version("1.4.0");
+        let fossology = mock.fossology();
+
+        assert!(matches!(
+            get_upload_permissions(&fossology, 1),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::UploadPermissions,
+                ..
+            })
+        ));
+    }
+}
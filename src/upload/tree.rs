@@ -0,0 +1,294 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Browse the unpacked contents of uploads.
+
+use std::io::Write;
+
+use serde::Deserialize;
+
+use crate::{
+    copy_response_to, total_pages, version::Capability, Fossology, FossologyError,
+    FossologyResponse, Page, Paginator,
+};
+
+use super::{get_top_item, Findings, Hash};
+
+/// Get one page of the direct children of an item in the upload's file tree.
+///
+/// # Errors
+///
+/// - Tree view is not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response can't be serialized to [`Vec`] of [`TreeItem`]s or [`Info`](crate::Info).
+/// - Response is not [`Vec`] of [`TreeItem`]s.
+pub fn get_children(
+    fossology: &Fossology,
+    upload_id: i32,
+    item_id: i32,
+    limit: Option<i32>,
+    page: Option<i32>,
+) -> Result<TreePage, FossologyError> {
+    fossology.require(Capability::TreeView)?;
+
+    let mut builder =
+        fossology.init_get_with_token(&format!("uploads/{}/item/{}/tree/view", upload_id, item_id));
+
+    builder = if let Some(limit) = limit {
+        builder.param("limit", limit)
+    } else {
+        builder
+    };
+
+    builder = if let Some(page) = page {
+        builder.param("page", page)
+    } else {
+        builder
+    };
+
+    let response = builder.send()?;
+
+    let total_pages = total_pages(&response);
+
+    let response = response.json::<FossologyResponse<Vec<TreeItem>>>()?;
+
+    match response {
+        FossologyResponse::Response(items) => Ok(TreePage { items, total_pages }),
+        FossologyResponse::ApiError(err) => Err(FossologyError::Other(err.message)),
+    }
+}
+
+/// Download a single file of the upload into `writer`.
+///
+/// The content is streamed, so the file is not buffered in memory. Returns the number of bytes
+/// written.
+///
+/// # Errors
+///
+/// - Item download is not supported by the API version.
+/// - Error while sending request, redirect loop was detected or redirect limit was exhausted.
+/// - Response is not successful.
+/// - Writing to `writer` fails.
+pub fn download_item<W: Write + ?Sized>(
+    fossology: &Fossology,
+    upload_id: i32,
+    item_id: i32,
+    writer: &mut W,
+) -> Result<u64, FossologyError> {
+    fossology.require(Capability::ItemDownload)?;
+
+    let response = fossology
+        .init_get_with_token(&format!("uploads/{}/item/{}/download", upload_id, item_id))
+        .send()?;
+
+    copy_response_to(response, writer)
+}
+
+/// Walk the whole file tree of the upload recursively, starting from its top level item.
+///
+/// # Errors
+///
+/// - Tree view is not supported by the API version.
+/// - Top level item of the upload can't be retrieved.
+pub fn walk(fossology: &Fossology, upload_id: i32) -> Result<TreeWalker<'_>, FossologyError> {
+    fossology.require(Capability::TreeView)?;
+
+    let top_item = get_top_item(fossology, upload_id)?;
+
+    Ok(TreeWalker::new(fossology, upload_id, top_item))
+}
+
+/// Page of items in a directory of the upload.
+pub type TreePage = Page<TreeItem>;
+
+/// File or directory in the upload.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeItem {
+    pub item_id: i32,
+
+    pub file_name: String,
+
+    /// Directories and unpacked containers have children.
+    #[serde(default)]
+    pub is_directory: bool,
+
+    /// Hashes and size of the file. Not available for directories.
+    #[serde(default)]
+    pub hash: Option<Hash>,
+
+    #[serde(default)]
+    pub findings: Option<Findings>,
+}
+
+/// Item of the upload with its path from the top level item.
+#[derive(Debug)]
+pub struct TreeEntry {
+    /// Path of the item, separated with `/`.
+    pub path: String,
+
+    pub item: TreeItem,
+}
+
+/// Iterator over all items in the upload's file tree. Created with [`walk`].
+///
+/// Directories are listed in pages, so large directories are retrieved with multiple requests.
+pub struct TreeWalker<'a> {
+    fossology: &'a Fossology,
+    upload_id: i32,
+    page_size: i32,
+
+    /// Directories being listed with their paths. The most recently found directory is last.
+    directories: Vec<(String, Paginator<'a, TreeItem>)>,
+}
+
+impl<'a> TreeWalker<'a> {
+    /// Walk the tree below the given item.
+    pub fn new(fossology: &'a Fossology, upload_id: i32, item_id: i32) -> Self {
+        let mut walker = Self {
+            fossology,
+            upload_id,
+            page_size: Paginator::<TreeItem>::DEFAULT_PAGE_SIZE,
+            directories: Vec::new(),
+        };
+        walker.enter(String::new(), item_id);
+        walker
+    }
+
+    /// Set the number of items requested per page.
+    #[must_use]
+    pub fn page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size;
+        self.directories = self
+            .directories
+            .into_iter()
+            .map(|(path, children)| (path, children.page_size(page_size)))
+            .collect();
+        self
+    }
+
+    /// List the children of the directory next.
+    fn enter(&mut self, path: String, item_id: i32) {
+        let (fossology, upload_id) = (self.fossology, self.upload_id);
+        let children = Paginator::new(move |limit, page| {
+            get_children(fossology, upload_id, item_id, Some(limit), Some(page))
+        })
+        .page_size(self.page_size);
+
+        self.directories.push((path, children));
+    }
+}
+
+impl Iterator for TreeWalker<'_> {
+    type Item = Result<TreeEntry, FossologyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (directory, children) = self.directories.last_mut()?;
+
+            let item = match children.next() {
+                Some(Ok(item)) => item,
+                Some(Err(err)) => {
+                    self.directories.clear();
+                    return Some(Err(err));
+                }
+                None => {
+                    self.directories.pop();
+                    continue;
+                }
+            };
+
+            let path = if directory.is_empty() {
+                item.file_name.clone()
+            } else {
+                format!("{}/{}", directory, item.file_name)
+            };
+
+            if item.is_directory {
+                self.enter(path.clone(), item.item_id);
+            }
+
+            return Some(Ok(TreeEntry { path, item }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{testing::MockFossology, upload::new_upload_from_file};
+
+    use super::*;
+
+    #[test]
+    fn walk_upload_tree() {
+        let mock = MockFossology::start();
+        let fossology = mock.fossology();
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+
+        let entries = walk(&fossology, upload.upload_id)
+            .unwrap()
+            .page_size(5)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let archive = entries
+            .iter()
+            .find(|entry| entry.path == "base-files_11.tar.xz")
+            .unwrap();
+
+        assert!(!archive.item.is_directory);
+        assert!(archive.item.hash.as_ref().unwrap().sha256.is_some());
+
+        let mut content = Vec::new();
+        download_item(
+            &fossology,
+            upload.upload_id,
+            archive.item.item_id,
+            &mut content,
+        )
+        .unwrap();
+
+        assert_eq!(
+            content,
+            std::fs::read("tests/data/base-files_11.tar.xz").unwrap()
+        );
+    }
+
+    #[test]
+    fn tree_view_and_item_download_require_version() {
+        let mock = MockFossology::with_version("1.5.1");
+        let fossology = mock.fossology();
+
+        let upload =
+            new_upload_from_file(&fossology, 1, "tests/data/base-files_11.tar.xz").unwrap();
+        let item_id = walk(&fossology, upload.upload_id)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .item
+            .item_id;
+
+        assert!(matches!(
+            download_item(&fossology, upload.upload_id, item_id, &mut Vec::new()),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::ItemDownload,
+                ..
+            })
+        ));
+
+        let mock = MockFossology::with_version("1.5.0");
+        let fossology = mock.fossology();
+
+        assert!(matches!(
+            walk(&fossology, upload.upload_id),
+            Err(FossologyError::UnsupportedVersion {
+                capability: Capability::TreeView,
+                ..
+            })
+        ));
+    }
+}
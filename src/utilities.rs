@@ -3,17 +3,128 @@
 // SPDX-License-Identifier: MIT
 
 #[cfg(test)]
-use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{de, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
 #[cfg(test)]
-use std::{fs::File, io, path::Path};
+use crate::upload::Hash;
 
 #[cfg(test)]
 pub fn hash256_for_path<P: AsRef<Path>>(path: P) -> String {
-    let mut file = File::open(path).unwrap();
-    let mut sha256 = Sha256::new();
-    io::copy(&mut file, &mut sha256).unwrap();
-    let hash: sha2::digest::generic_array::GenericArray<u8, <Sha256 as Digest>::OutputSize> =
-        sha256.finalize();
+    Hash::from_file(path).unwrap().sha256.unwrap()
+}
+
+/// Converts the `snake_case` keys of the objects in `value` to camelCase.
+pub fn camel_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (camel_case(&key), camel_case_keys(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(camel_case_keys).collect()),
+        value => value,
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut parts = key.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+/// Parses the date-times of the API. Different versions use RFC 3339 and the database's format
+/// with or without fractional seconds and with short (`+02`) or full (`+02:00`) offsets. Dates
+/// without an offset are in UTC.
+pub fn parse_date_time(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S%.f%#z"))
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|date| Utc.from_utc_datetime(&date))
+        })
+}
+
+pub fn deserialize_date_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateTime<Utc>, D::Error> {
+    let date = String::deserialize(deserializer)?;
+
+    parse_date_time(&date).ok_or_else(|| de::Error::custom(format!("invalid date-time: {}", date)))
+}
+
+/// Deserializes date-times that can be missing, `null` or empty.
+pub fn deserialize_optional_date_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(date) if !date.trim().is_empty() => parse_date_time(&date)
+            .map(Some)
+            .ok_or_else(|| de::Error::custom(format!("invalid date-time: {}", date))),
+        _ => Ok(None),
+    }
+}
+
+/// Deserializes ids that are numbers in some versions of the API and strings in others.
+pub fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(i32),
+        String(String),
+    }
+
+    match Id::deserialize(deserializer)? {
+        Id::Number(id) => Ok(id),
+        Id::String(id) => id
+            .trim()
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid id: {}", id))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn date_times_of_different_versions_are_parsed() {
+        let expected = Utc.with_ymd_and_hms(2021, 3, 3, 8, 13, 49).unwrap();
+
+        for date in [
+            "2021-03-03 10:13:49+02",
+            "2021-03-03 10:13:49+02:00",
+            "2021-03-03T10:13:49+02:00",
+            "2021-03-03T08:13:49Z",
+            "2021-03-03 08:13:49",
+        ] {
+            assert_eq!(parse_date_time(date), Some(expected), "{}", date);
+        }
 
-    hex::encode_upper(hash)
+        assert_eq!(
+            parse_date_time("2021-03-03 10:13:49.187446+02")
+                .unwrap()
+                .timestamp_subsec_micros(),
+            187_446
+        );
+        assert_eq!(parse_date_time("yesterday"), None);
+    }
 }
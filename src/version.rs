@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: 2021 HH Partners
+//
+// SPDX-License-Identifier: MIT
+
+//! Versions of the API and the features they support.
+
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::FossologyError;
+
+/// Version of the Fossology API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ApiVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ApiVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Parses versions like `1.4.0`. Missing minor and patch versions are zero and pre-release or
+/// build suffixes like `-rc1` are ignored.
+impl FromStr for ApiVersion {
+    type Err = FossologyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FossologyError::Other(format!("Invalid API version: {}", s));
+
+        let version = s.trim().trim_start_matches('v');
+        let version = version.split(['-', '+']).next().unwrap_or_default();
+
+        let mut parts = version.split('.').map(str::parse::<u32>);
+        let major = parts.next().ok_or_else(invalid)?.map_err(|_| invalid())?;
+        let minor = parts.next().transpose().map_err(|_| invalid())?;
+        let patch = parts.next().transpose().map_err(|_| invalid())?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(
+            major,
+            minor.unwrap_or_default(),
+            patch.unwrap_or_default(),
+        ))
+    }
+}
+
+impl TryFrom<String> for ApiVersion {
+    type Error = FossologyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ApiVersion> for String {
+    fn from(version: ApiVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// Feature of the API that is not available in all versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// `GET /info`.
+    ApiInfo,
+
+    /// `GET /health`.
+    Health,
+
+    /// License short name is given in the path of `GET /license/{shortName}` instead of a header.
+    LicensePathParam,
+
+    /// `POST /uploads/{id}/item/{itemId}/bulk-scan`.
+    BulkScan,
+
+    /// `GET /uploads/{id}/item/{itemId}/tree/view`.
+    TreeView,
+
+    /// `GET /uploads/{id}/item/{itemId}/download`.
+    ItemDownload,
+
+    /// `GET /search`.
+    Search,
+
+    /// `GET /report` and `GET /report/{id}`.
+    Report,
+
+    /// `GET /jobs/all`.
+    AllJobs,
+
+    /// Killing, pausing, resuming and changing the priority of jobs.
+    JobControl,
+
+    /// `heritage` agent that looks up the files from Software Heritage.
+    SoftwareHeritage,
+
+    /// `scancode` agent that runs scancode-toolkit.
+    ScancodeAgent,
+
+    /// `compatibility` agent that checks the compatibility of licenses.
+    CompatibilityAgent,
+
+    /// `ipra` agent that finds intellectual property rights related statements.
+    IpraAgent,
+
+    /// Reading and changing the permissions of uploads.
+    UploadPermissions,
+
+    /// API v2, where the parameters are sent as query parameters instead of headers and the
+    /// payloads are camelCase.
+    ApiV2,
+}
+
+impl Capability {
+    /// All of the capabilities.
+    pub const ALL: &'static [Self] = &[
+        Self::ApiInfo,
+        Self::Health,
+        Self::LicensePathParam,
+        Self::BulkScan,
+        Self::TreeView,
+        Self::ItemDownload,
+        Self::Search,
+        Self::Report,
+        Self::AllJobs,
+        Self::JobControl,
+        Self::SoftwareHeritage,
+        Self::ScancodeAgent,
+        Self::CompatibilityAgent,
+        Self::IpraAgent,
+        Self::UploadPermissions,
+        Self::ApiV2,
+    ];
+
+    /// Oldest version of the API that supports the capability.
+    pub const fn required_version(self) -> ApiVersion {
+        match self {
+            Self::Search | Self::Report => ApiVersion::new(1, 0, 16),
+            Self::SoftwareHeritage => ApiVersion::new(1, 1, 1),
+            Self::LicensePathParam => ApiVersion::new(1, 3, 0),
+            Self::ApiInfo | Self::Health => ApiVersion::new(1, 3, 3),
+            Self::AllJobs | Self::ScancodeAgent => ApiVersion::new(1, 5, 0),
+            Self::BulkScan | Self::TreeView | Self::UploadPermissions => ApiVersion::new(1, 5, 1),
+            Self::JobControl | Self::CompatibilityAgent | Self::IpraAgent | Self::ItemDownload => {
+                ApiVersion::new(1, 6, 0)
+            }
+            Self::ApiV2 => ApiVersion::new(2, 0, 0),
+        }
+    }
+
+    /// Returns true if the capability is available in `version` of the API.
+    pub fn is_supported_by(self, version: ApiVersion) -> bool {
+        version >= self.required_version()
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::ApiInfo => "API information",
+            Self::Health => "health check",
+            Self::LicensePathParam => "license short name as path parameter",
+            Self::BulkScan => "bulk scan",
+            Self::TreeView => "upload tree view",
+            Self::ItemDownload => "item download",
+            Self::Search => "search",
+            Self::Report => "reports",
+            Self::AllJobs => "listing jobs of all users",
+            Self::JobControl => "job control",
+            Self::SoftwareHeritage => "Software Heritage agent",
+            Self::ScancodeAgent => "scancode agent",
+            Self::CompatibilityAgent => "compatibility agent",
+            Self::IpraAgent => "ipra agent",
+            Self::UploadPermissions => "upload permissions",
+            Self::ApiV2 => "API v2",
+        };
+
+        write!(f, "{}", description)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn versions_are_parsed() {
+        assert_eq!(
+            "1.4.0".parse::<ApiVersion>().unwrap(),
+            ApiVersion::new(1, 4, 0)
+        );
+        assert_eq!(
+            "v1.3".parse::<ApiVersion>().unwrap(),
+            ApiVersion::new(1, 3, 0)
+        );
+        assert_eq!(
+            "1.5.1-rc1".parse::<ApiVersion>().unwrap(),
+            ApiVersion::new(1, 5, 1)
+        );
+        assert!("".parse::<ApiVersion>().is_err());
+        assert!("1.x.0".parse::<ApiVersion>().is_err());
+        assert!("1.2.3.4".parse::<ApiVersion>().is_err());
+    }
+
+    #[test]
+    fn versions_are_ordered_numerically() {
+        assert!(ApiVersion::new(1, 10, 0) > ApiVersion::new(1, 9, 9));
+        assert!(ApiVersion::new(2, 0, 0) > ApiVersion::new(1, 99, 99));
+    }
+
+    #[test]
+    fn capabilities_require_version() {
+        assert!(!Capability::Health.is_supported_by(ApiVersion::new(1, 3, 2)));
+        assert!(Capability::Health.is_supported_by(ApiVersion::new(1, 3, 3)));
+        assert!(Capability::LicensePathParam.is_supported_by(ApiVersion::new(1, 4, 0)));
+        assert!(!Capability::BulkScan.is_supported_by(ApiVersion::new(1, 4, 0)));
+        assert!(Capability::Search.is_supported_by(ApiVersion::new(1, 2, 0)));
+        assert!(!Capability::ItemDownload.is_supported_by(ApiVersion::new(1, 5, 1)));
+    }
+}